@@ -0,0 +1,577 @@
+//! A stand-in for the Tavily API that serves fixture responses.
+//!
+//! Start it in a test and point `TavilyConfig::base_url` at [`MockTavily::base_url`]:
+//!
+//! ```no_run
+//! # async fn run() -> anyhow::Result<()> {
+//! use mock_tavily::{Fault, MockTavily};
+//!
+//! let mock = MockTavily::start().await?;
+//! mock.fail_next("search", Fault::RateLimited);
+//! // ... call the server with `base_url = mock.base_url()` ...
+//! assert_eq!(mock.requests().len(), 1);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Faults can also be triggered without access to the handle by using one of
+//! the magic API keys `tvly-mock-401`, `tvly-mock-429`, `tvly-mock-500` or
+//! `tvly-mock-malformed`.
+//!
+//! Instead of fixtures, the mock can proxy to the real API and save every
+//! exchange with [`MockTavilyBuilder::record`], then serve the saved responses
+//! with [`MockTavilyBuilder::replay`]. See [`recording`].
+
+pub mod recording;
+
+use anyhow::{Context, Result, bail};
+use axum::body::Bytes;
+use axum::extract::{Path, State};
+use axum::http::{HeaderMap, StatusCode, header};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Router, Server};
+use serde_json::{Value, json};
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::net::{SocketAddr, TcpListener};
+use std::path::{Path as FsPath, PathBuf};
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::oneshot;
+use tracing::{debug, info, warn};
+
+use crate::recording::{Recording, Recordings, normalize};
+
+/// Endpoints with a built-in fixture.
+pub const ENDPOINTS: [&str; 5] = ["search", "extract", "crawl", "map", "usage"];
+
+const MAGIC_KEY_PREFIX: &str = "tvly-mock-";
+
+/// A failure the mock injects instead of, or before, the fixture response.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Fault {
+    /// 401 with Tavily's invalid key error.
+    Unauthorized,
+    /// 429 with Tavily's rate limit error.
+    RateLimited,
+    /// 500 with a generic error.
+    ServerError,
+    /// 200 with a body that is not valid JSON.
+    MalformedJson,
+    /// Waits before answering normally.
+    Delay(Duration),
+}
+
+impl FromStr for Fault {
+    type Err = anyhow::Error;
+
+    /// Parses `401`, `429`, `500`, `malformed` or `delay:<ms>`.
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "401" | "unauthorized" => Ok(Fault::Unauthorized),
+            "429" | "rate-limited" => Ok(Fault::RateLimited),
+            "500" | "server-error" => Ok(Fault::ServerError),
+            "malformed" => Ok(Fault::MalformedJson),
+            other => match other.strip_prefix("delay:") {
+                Some(ms) => Ok(Fault::Delay(Duration::from_millis(
+                    ms.parse()
+                        .with_context(|| format!("Invalid delay '{}'", ms))?,
+                ))),
+                None => bail!("Unknown fault '{}'", other),
+            },
+        }
+    }
+}
+
+impl fmt::Display for Fault {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Fault::Unauthorized => write!(f, "401"),
+            Fault::RateLimited => write!(f, "429"),
+            Fault::ServerError => write!(f, "500"),
+            Fault::MalformedJson => write!(f, "malformed"),
+            Fault::Delay(delay) => write!(f, "delay:{}", delay.as_millis()),
+        }
+    }
+}
+
+/// A request received by the mock.
+#[derive(Debug, Clone)]
+pub struct Request {
+    pub endpoint: String,
+    /// The bearer token or `api_key` field the request was authorized with.
+    pub api_key: Option<String>,
+    pub body: Value,
+}
+
+#[derive(Default)]
+struct MockState {
+    fixtures: Mutex<HashMap<String, Value>>,
+    faults: Mutex<HashMap<String, Fault>>,
+    queued: Mutex<HashMap<String, VecDeque<Fault>>>,
+    latency: Mutex<Duration>,
+    requests: Mutex<Vec<Request>>,
+    mode: Mode,
+}
+
+// Where responses come from once faults have been applied
+#[derive(Default)]
+enum Mode {
+    #[default]
+    Fixtures,
+    Record {
+        upstream: String,
+        dir: PathBuf,
+        client: reqwest::Client,
+    },
+    Replay(Recordings),
+}
+
+/// Configures a mock before it starts listening.
+pub struct MockTavilyBuilder {
+    listen: SocketAddr,
+    state: MockState,
+}
+
+impl MockTavilyBuilder {
+    /// Defaults to an ephemeral port on localhost.
+    pub fn listen(mut self, listen: SocketAddr) -> Self {
+        self.listen = listen;
+        self
+    }
+
+    /// Replaces the fixture for `endpoint`.
+    pub fn fixture(self, endpoint: &str, response: Value) -> Self {
+        self.state
+            .fixtures
+            .lock()
+            .unwrap()
+            .insert(endpoint.to_string(), response);
+        self
+    }
+
+    /// Loads `<endpoint>.json` files from `dir`, replacing the built-in fixtures.
+    pub fn fixtures_dir(mut self, dir: &FsPath) -> Result<Self> {
+        for entry in std::fs::read_dir(dir)
+            .with_context(|| format!("Failed to read fixtures from {}", dir.display()))?
+        {
+            let path = entry?.path();
+            if path.extension().is_none_or(|ext| ext != "json") {
+                continue;
+            }
+            let Some(endpoint) = path.file_stem().and_then(|s| s.to_str()) else {
+                continue;
+            };
+
+            let text = std::fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read {}", path.display()))?;
+            let response = serde_json::from_str(&text)
+                .with_context(|| format!("Invalid fixture {}", path.display()))?;
+            self = self.fixture(endpoint, response);
+        }
+        Ok(self)
+    }
+
+    /// Applies `fault` to every request to `endpoint`.
+    pub fn fault(self, endpoint: &str, fault: Fault) -> Self {
+        self.state
+            .faults
+            .lock()
+            .unwrap()
+            .insert(endpoint.to_string(), fault);
+        self
+    }
+
+    /// Delays every response by `latency`.
+    pub fn latency(self, latency: Duration) -> Self {
+        *self.state.latency.lock().unwrap() = latency;
+        self
+    }
+
+    /// Forwards requests to `upstream` (e.g. `https://api.tavily.com`) with the
+    /// caller's API key and saves each request/response pair to `dir`.
+    pub fn record(mut self, upstream: &str, dir: &FsPath) -> Self {
+        self.state.mode = Mode::Record {
+            upstream: upstream.trim_end_matches('/').to_string(),
+            dir: dir.to_path_buf(),
+            client: reqwest::Client::new(),
+        };
+        self
+    }
+
+    /// Serves the recordings in `dir`. Requests without a matching recording
+    /// get a 404 rather than a fixture, so drift is noticed.
+    pub fn replay(mut self, dir: &FsPath) -> Result<Self> {
+        self.state.mode = Mode::Replay(Recordings::load(dir)?);
+        Ok(self)
+    }
+
+    pub async fn start(self) -> Result<MockTavily> {
+        let listener = TcpListener::bind(self.listen)
+            .with_context(|| format!("Failed to bind mock Tavily to {}", self.listen))?;
+        listener.set_nonblocking(true)?;
+        let addr = listener.local_addr()?;
+
+        let state = Arc::new(self.state);
+        let app = Router::new()
+            .route("/usage", get(usage))
+            .route("/:endpoint", post(endpoint))
+            .with_state(state.clone());
+
+        let (shutdown, stopped) = oneshot::channel::<()>();
+        let server = Server::from_tcp(listener)?
+            .serve(app.into_make_service())
+            .with_graceful_shutdown(async move {
+                stopped.await.ok();
+            });
+        tokio::spawn(server);
+
+        info!("Mock Tavily API listening on http://{}", addr);
+        Ok(MockTavily {
+            addr,
+            state,
+            shutdown: Some(shutdown),
+        })
+    }
+}
+
+/// A running mock; it stops when dropped.
+pub struct MockTavily {
+    addr: SocketAddr,
+    state: Arc<MockState>,
+    shutdown: Option<oneshot::Sender<()>>,
+}
+
+impl MockTavily {
+    pub fn builder() -> MockTavilyBuilder {
+        let state = MockState::default();
+        *state.fixtures.lock().unwrap() = default_fixtures();
+
+        MockTavilyBuilder {
+            listen: SocketAddr::from(([127, 0, 0, 1], 0)),
+            state,
+        }
+    }
+
+    /// Starts a mock with the built-in fixtures on an ephemeral port.
+    pub async fn start() -> Result<Self> {
+        Self::builder().start().await
+    }
+
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// The value for `TavilyConfig::base_url` / `TAVILY_BASE_URL`.
+    pub fn base_url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    /// Applies `fault` to the next request to `endpoint` only. Calls queue up.
+    pub fn fail_next(&self, endpoint: &str, fault: Fault) {
+        self.state
+            .queued
+            .lock()
+            .unwrap()
+            .entry(endpoint.to_string())
+            .or_default()
+            .push_back(fault);
+    }
+
+    /// Applies `fault` to every request to `endpoint` until cleared with `None`.
+    pub fn set_fault(&self, endpoint: &str, fault: Option<Fault>) {
+        let mut faults = self.state.faults.lock().unwrap();
+        match fault {
+            Some(fault) => faults.insert(endpoint.to_string(), fault),
+            None => faults.remove(endpoint),
+        };
+    }
+
+    pub fn set_latency(&self, latency: Duration) {
+        *self.state.latency.lock().unwrap() = latency;
+    }
+
+    pub fn set_fixture(&self, endpoint: &str, response: Value) {
+        self.state
+            .fixtures
+            .lock()
+            .unwrap()
+            .insert(endpoint.to_string(), response);
+    }
+
+    /// Requests received so far, oldest first, including those that were failed.
+    pub fn requests(&self) -> Vec<Request> {
+        self.state.requests.lock().unwrap().clone()
+    }
+
+    /// Requests received so far for `endpoint`.
+    pub fn requests_to(&self, endpoint: &str) -> Vec<Request> {
+        self.requests()
+            .into_iter()
+            .filter(|request| request.endpoint == endpoint)
+            .collect()
+    }
+}
+
+impl Drop for MockTavily {
+    fn drop(&mut self) {
+        if let Some(shutdown) = self.shutdown.take() {
+            shutdown.send(()).ok();
+        }
+    }
+}
+
+/// The fixtures compiled into the crate, keyed by endpoint.
+pub fn default_fixtures() -> HashMap<String, Value> {
+    [
+        ("search", include_str!("../fixtures/search.json")),
+        ("extract", include_str!("../fixtures/extract.json")),
+        ("crawl", include_str!("../fixtures/crawl.json")),
+        ("map", include_str!("../fixtures/map.json")),
+        ("usage", include_str!("../fixtures/usage.json")),
+    ]
+    .into_iter()
+    .map(|(endpoint, text)| {
+        let fixture = serde_json::from_str(text).expect("built-in fixtures are valid JSON");
+        (endpoint.to_string(), fixture)
+    })
+    .collect()
+}
+
+async fn usage(State(state): State<Arc<MockState>>, headers: HeaderMap) -> Response {
+    respond(&state, "usage", &headers, Value::Null).await
+}
+
+async fn endpoint(
+    State(state): State<Arc<MockState>>,
+    Path(endpoint): Path<String>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Response {
+    let Ok(body) = serde_json::from_slice::<Value>(&body) else {
+        return error(StatusCode::BAD_REQUEST, "Request body must be JSON");
+    };
+    respond(&state, &endpoint, &headers, body).await
+}
+
+async fn respond(state: &MockState, endpoint: &str, headers: &HeaderMap, body: Value) -> Response {
+    let api_key = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .or_else(|| body.get("api_key").and_then(|key| key.as_str()))
+        .map(str::to_string);
+    debug!("Mock Tavily {} request: {}", endpoint, body);
+
+    state.requests.lock().unwrap().push(Request {
+        endpoint: endpoint.to_string(),
+        api_key: api_key.clone(),
+        body: body.clone(),
+    });
+
+    let latency = *state.latency.lock().unwrap();
+    if !latency.is_zero() {
+        tokio::time::sleep(latency).await;
+    }
+
+    let magic = api_key
+        .as_deref()
+        .and_then(|key| key.strip_prefix(MAGIC_KEY_PREFIX))
+        .and_then(|fault| fault.parse().ok());
+    let queued = state
+        .queued
+        .lock()
+        .unwrap()
+        .get_mut(endpoint)
+        .and_then(|queue| queue.pop_front());
+    let sticky = state.faults.lock().unwrap().get(endpoint).copied();
+
+    match magic.or(queued).or(sticky) {
+        Some(Fault::Unauthorized) => {
+            return error(
+                StatusCode::UNAUTHORIZED,
+                "Unauthorized: missing or invalid API key.",
+            );
+        }
+        Some(Fault::RateLimited) => {
+            return error(
+                StatusCode::TOO_MANY_REQUESTS,
+                "Your request has been blocked due to excessive requests.",
+            );
+        }
+        Some(Fault::ServerError) => {
+            return error(StatusCode::INTERNAL_SERVER_ERROR, "Internal server error");
+        }
+        Some(Fault::MalformedJson) => {
+            return (
+                [(header::CONTENT_TYPE, "application/json")],
+                "{\"results\": [{\"title\": ",
+            )
+                .into_response();
+        }
+        Some(Fault::Delay(delay)) => tokio::time::sleep(delay).await,
+        None => {}
+    }
+
+    if api_key.is_none() {
+        return error(
+            StatusCode::UNAUTHORIZED,
+            "Unauthorized: missing or invalid API key.",
+        );
+    }
+
+    match &state.mode {
+        Mode::Fixtures => {
+            if let Some(message) = validate(endpoint, &body) {
+                return error(StatusCode::BAD_REQUEST, message);
+            }
+        }
+        Mode::Record {
+            upstream,
+            dir,
+            client,
+        } => return forward(client, upstream, dir, endpoint, api_key.as_deref(), &body).await,
+        Mode::Replay(recordings) => {
+            return match recordings.find(endpoint, &body) {
+                Some(recording) => replay(recording),
+                None => error(
+                    StatusCode::NOT_FOUND,
+                    &format!("No recording for {} request {}", endpoint, normalize(&body)),
+                ),
+            };
+        }
+    }
+
+    let Some(fixture) = state.fixtures.lock().unwrap().get(endpoint).cloned() else {
+        return error(StatusCode::NOT_FOUND, "Not Found");
+    };
+    axum::Json(shape(endpoint, fixture, &body)).into_response()
+}
+
+// Passes the request on to the real API and records the exchange
+async fn forward(
+    client: &reqwest::Client,
+    upstream: &str,
+    dir: &FsPath,
+    endpoint: &str,
+    api_key: Option<&str>,
+    body: &Value,
+) -> Response {
+    let url = format!("{}/{}", upstream, endpoint);
+    let mut request = match body {
+        Value::Null => client.get(&url),
+        body => client.post(&url).json(body),
+    };
+    if let Some(api_key) = api_key {
+        request = request.bearer_auth(api_key);
+    }
+
+    let response = match request.send().await {
+        Ok(response) => response,
+        Err(e) => {
+            return error(
+                StatusCode::BAD_GATEWAY,
+                &format!("Upstream request failed: {}", e),
+            );
+        }
+    };
+    let status = response.status().as_u16();
+    let text = response.text().await.unwrap_or_default();
+
+    let recording = Recording::new(endpoint, body, status, &text);
+    if let Err(e) = recording.save(dir) {
+        warn!("Failed to save recording: {:#}", e);
+    }
+
+    (
+        StatusCode::from_u16(status).unwrap_or(StatusCode::BAD_GATEWAY),
+        [(header::CONTENT_TYPE, "application/json")],
+        text,
+    )
+        .into_response()
+}
+
+fn replay(recording: &Recording) -> Response {
+    let status = StatusCode::from_u16(recording.status).unwrap_or(StatusCode::OK);
+    match (&recording.response, recording.raw) {
+        (Value::String(text), true) => (
+            status,
+            [(header::CONTENT_TYPE, "application/json")],
+            text.clone(),
+        )
+            .into_response(),
+        (response, _) => (status, axum::Json(response.clone())).into_response(),
+    }
+}
+
+// Rejects requests Tavily would reject for a missing parameter
+fn validate(endpoint: &str, body: &Value) -> Option<&'static str> {
+    let present = |field: &str| match body.get(field) {
+        Some(Value::String(text)) => !text.trim().is_empty(),
+        Some(Value::Array(items)) => !items.is_empty(),
+        _ => false,
+    };
+
+    match endpoint {
+        "search" if !present("query") => Some("Query is missing."),
+        "extract" if !present("urls") => Some("URLs are missing."),
+        "crawl" | "map" if !present("url") => Some("URL is missing."),
+        _ => None,
+    }
+}
+
+// Fills the fixture in from the request so responses line up with what was asked
+fn shape(endpoint: &str, mut fixture: Value, body: &Value) -> Value {
+    if !fixture.is_object() {
+        return fixture;
+    }
+
+    match endpoint {
+        "search" => {
+            if let Some(query) = body.get("query") {
+                fixture["query"] = query.clone();
+            }
+            if let Some(max) = body.get("max_results").and_then(|m| m.as_u64())
+                && let Some(results) = fixture["results"].as_array_mut()
+            {
+                results.truncate(max as usize);
+            }
+        }
+        "extract" => {
+            let urls: Vec<Value> = match body.get("urls") {
+                Some(Value::Array(urls)) => urls.clone(),
+                Some(url @ Value::String(_)) => vec![url.clone()],
+                _ => Vec::new(),
+            };
+            let templates = fixture["results"].as_array().cloned().unwrap_or_default();
+            if !templates.is_empty() {
+                fixture["results"] = urls
+                    .into_iter()
+                    .enumerate()
+                    .map(|(index, url)| {
+                        let mut result = templates[index % templates.len()].clone();
+                        result["url"] = url;
+                        result
+                    })
+                    .collect();
+            }
+        }
+        "crawl" | "map" => {
+            if let Some(url) = body.get("url") {
+                fixture["base_url"] = url.clone();
+            }
+        }
+        _ => {}
+    }
+    fixture
+}
+
+fn error(status: StatusCode, message: &str) -> Response {
+    (
+        status,
+        axum::Json(json!({ "detail": { "error": message } })),
+    )
+        .into_response()
+}
@@ -0,0 +1,79 @@
+use anyhow::{Context, Result};
+use clap::Parser;
+use mock_tavily::{Fault, MockTavily};
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::time::Duration;
+use tracing::info;
+use tracing_subscriber::EnvFilter;
+
+/// Serves fixture responses in place of the Tavily API.
+///
+/// Point the server at it with TAVILY_BASE_URL=http://<listen>. Any API key is
+/// accepted; the keys tvly-mock-401, tvly-mock-429, tvly-mock-500 and
+/// tvly-mock-malformed trigger the matching failure.
+#[derive(Parser)]
+#[command(name = "mock-tavily", version)]
+struct Cli {
+    /// Address to listen on
+    #[arg(long, default_value = "127.0.0.1:8787")]
+    listen: SocketAddr,
+
+    /// Directory of <endpoint>.json files replacing the built-in fixtures
+    #[arg(long)]
+    fixtures: Option<PathBuf>,
+
+    /// Fail every request to an endpoint, e.g. search=429, extract=malformed or map=delay:2000
+    #[arg(long = "fault", value_name = "ENDPOINT=FAULT")]
+    faults: Vec<String>,
+
+    /// Delay every response by this many milliseconds
+    #[arg(long, default_value_t = 0)]
+    latency_ms: u64,
+
+    /// Proxy to the real API and save each request/response pair to this directory
+    #[arg(long, value_name = "DIR", conflicts_with = "replay")]
+    record: Option<PathBuf>,
+
+    /// API that --record forwards to
+    #[arg(long, default_value = "https://api.tavily.com")]
+    upstream: String,
+
+    /// Serve the recordings in this directory instead of fixtures
+    #[arg(long, value_name = "DIR")]
+    replay: Option<PathBuf>,
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::try_from_default_env().unwrap_or_else(|_| "info".into()))
+        .init();
+    let cli = Cli::parse();
+
+    let mut builder = MockTavily::builder()
+        .listen(cli.listen)
+        .latency(Duration::from_millis(cli.latency_ms));
+    if let Some(dir) = &cli.fixtures {
+        builder = builder.fixtures_dir(dir)?;
+    }
+    if let Some(dir) = &cli.record {
+        builder = builder.record(&cli.upstream, dir);
+    }
+    if let Some(dir) = &cli.replay {
+        builder = builder.replay(dir)?;
+    }
+    for fault in &cli.faults {
+        let (endpoint, fault) = fault
+            .split_once('=')
+            .with_context(|| format!("Expected ENDPOINT=FAULT, got '{}'", fault))?;
+        builder = builder.fault(endpoint, fault.parse::<Fault>()?);
+    }
+
+    let mock = builder.start().await?;
+    info!("Set TAVILY_BASE_URL={} to use it", mock.base_url());
+
+    tokio::signal::ctrl_c().await?;
+    info!("Stopping mock Tavily API");
+    Ok(())
+}
@@ -0,0 +1,195 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use tracing::{debug, info};
+
+/// Request fields that may carry a credential and never reach a recording.
+const SECRET_FIELDS: [&str; 2] = ["api_key", "authorization"];
+
+/// Parameters whose order does not change Tavily's answer.
+const UNORDERED_FIELDS: [&str; 3] = ["urls", "include_domains", "exclude_domains"];
+
+/// A request/response pair captured from the real Tavily API.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Recording {
+    pub endpoint: String,
+    /// The request body after `normalize`.
+    pub request: Value,
+    pub status: u16,
+    /// The response body, or the raw text when it was not JSON.
+    pub response: Value,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub raw: bool,
+}
+
+impl Recording {
+    /// Builds a recording with secrets scrubbed from both the request and the response.
+    pub fn new(endpoint: &str, request: &Value, status: u16, response_text: &str) -> Self {
+        let (mut response, raw) = match serde_json::from_str(response_text) {
+            Ok(response) => (response, false),
+            Err(_) => (Value::String(response_text.to_string()), true),
+        };
+        scrub(&mut response);
+
+        Self {
+            endpoint: endpoint.to_string(),
+            request: normalize(request),
+            status,
+            response,
+            raw,
+        }
+    }
+
+    pub fn key(&self) -> String {
+        request_key(&self.endpoint, &self.request)
+    }
+
+    /// Writes the recording to `<dir>/<endpoint>-<hash>.json`, replacing an
+    /// earlier recording of the same request.
+    pub fn save(&self, dir: &Path) -> Result<PathBuf> {
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create {}", dir.display()))?;
+        let path = dir.join(format!("{}.json", self.key()));
+        let text = serde_json::to_string_pretty(self)?;
+        std::fs::write(&path, text + "\n")
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+        info!(
+            "Recorded Tavily {} response to {}",
+            self.endpoint,
+            path.display()
+        );
+        Ok(path)
+    }
+}
+
+/// Recordings loaded from disk, matched by normalized request.
+#[derive(Debug, Default)]
+pub struct Recordings {
+    by_key: HashMap<String, Recording>,
+}
+
+impl Recordings {
+    /// Loads every `*.json` recording in `dir`.
+    pub fn load(dir: &Path) -> Result<Self> {
+        let mut by_key = HashMap::new();
+
+        for entry in std::fs::read_dir(dir)
+            .with_context(|| format!("Failed to read recordings from {}", dir.display()))?
+        {
+            let path = entry?.path();
+            if path.extension().is_none_or(|ext| ext != "json") {
+                continue;
+            }
+
+            let text = std::fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read {}", path.display()))?;
+            let recording: Recording = serde_json::from_str(&text)
+                .with_context(|| format!("Invalid recording {}", path.display()))?;
+            // Keys are recomputed so hand-edited requests still match
+            by_key.insert(recording.key(), recording);
+        }
+
+        info!(
+            "Loaded {} recording(s) from {}",
+            by_key.len(),
+            dir.display()
+        );
+        Ok(Self { by_key })
+    }
+
+    pub fn len(&self) -> usize {
+        self.by_key.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.by_key.is_empty()
+    }
+
+    pub fn find(&self, endpoint: &str, request: &Value) -> Option<&Recording> {
+        let key = request_key(endpoint, &normalize(request));
+        debug!("Looking up recording {}", key);
+        self.by_key.get(&key)
+    }
+}
+
+/// Canonical form of a request body: credentials and nulls removed, strings
+/// trimmed and order-insensitive lists sorted. Object keys are already sorted
+/// by `serde_json`, so equal requests serialize identically.
+pub fn normalize(request: &Value) -> Value {
+    let Value::Object(fields) = request else {
+        return request.clone();
+    };
+
+    let normalized = fields
+        .iter()
+        .filter(|(name, value)| {
+            !value.is_null() && !SECRET_FIELDS.contains(&name.to_ascii_lowercase().as_str())
+        })
+        .map(|(name, value)| {
+            let value = match value {
+                Value::String(text) => Value::String(text.trim().to_string()),
+                Value::Array(items) if UNORDERED_FIELDS.contains(&name.as_str()) => {
+                    let mut items: Vec<Value> = items.iter().map(normalize).collect();
+                    items.sort_by_key(|item| item.to_string());
+                    items.dedup();
+                    Value::Array(items)
+                }
+                Value::Array(items) => Value::Array(items.iter().map(normalize).collect()),
+                Value::Object(_) => normalize(value),
+                other => other.clone(),
+            };
+            (name.clone(), value)
+        })
+        .collect();
+    Value::Object(normalized)
+}
+
+/// File-name-safe identifier of a normalized request.
+pub fn request_key(endpoint: &str, normalized: &Value) -> String {
+    let canonical = format!("{} {}", endpoint, normalized);
+    format!("{}-{:016x}", endpoint, fnv1a(canonical.as_bytes()))
+}
+
+/// Replaces anything that looks like a Tavily API key in `value`.
+pub fn scrub(value: &mut Value) {
+    match value {
+        Value::String(text) if text.contains("tvly-") => *text = scrub_text(text),
+        Value::Array(items) => items.iter_mut().for_each(scrub),
+        Value::Object(fields) => {
+            for (name, field) in fields.iter_mut() {
+                if SECRET_FIELDS.contains(&name.to_ascii_lowercase().as_str()) {
+                    *field = Value::String("[REDACTED]".to_string());
+                } else {
+                    scrub(field);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+fn scrub_text(text: &str) -> String {
+    let mut output = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find("tvly-") {
+        output.push_str(&rest[..start]);
+        output.push_str("tvly-REDACTED");
+        let key = &rest[start + "tvly-".len()..];
+        let end = key
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '-' || c == '_'))
+            .unwrap_or(key.len());
+        rest = &key[end..];
+    }
+    output.push_str(rest);
+    output
+}
+
+// 64-bit FNV-1a, stable across builds and platforms
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x100000001b3)
+    })
+}
@@ -0,0 +1,53 @@
+use anyhow::{Context, Result};
+use axum::extract::State;
+use axum::http::{StatusCode, header};
+use axum::response::IntoResponse;
+use axum::routing::get;
+use axum::{Json, Router, Server};
+use std::net::SocketAddr;
+use tracing::info;
+
+use crate::health::Health;
+use crate::metrics::metrics;
+
+// HTTP endpoint for operational data, served alongside the NATS transport
+pub async fn serve(listen: &str, health: Health) -> Result<()> {
+    let addr: SocketAddr = listen
+        .parse()
+        .with_context(|| format!("Invalid admin listen address {}", listen))?;
+
+    let app = Router::new()
+        .route("/metrics", get(render_metrics))
+        .route("/healthz", get(liveness))
+        .route("/readyz", get(readiness))
+        .with_state(health);
+
+    info!("Serving metrics and health checks on http://{}", addr);
+    Server::try_bind(&addr)
+        .with_context(|| format!("Failed to bind admin server to {}", addr))?
+        .serve(app.into_make_service())
+        .await
+        .context("Admin server failed")
+}
+
+async fn render_metrics() -> impl IntoResponse {
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        metrics().render(),
+    )
+}
+
+// The process is alive as long as it can answer; the report is informational
+async fn liveness(State(health): State<Health>) -> impl IntoResponse {
+    Json(health.report().await)
+}
+
+async fn readiness(State(health): State<Health>) -> impl IntoResponse {
+    let report = health.report().await;
+    let status = if report.ready {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    (status, Json(report))
+}
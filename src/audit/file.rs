@@ -0,0 +1,87 @@
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tracing::info;
+
+use crate::audit::AuditSink;
+
+// JSONL file rotated to `<path>.1`, `<path>.2`, ... once it reaches `max_bytes`
+pub struct FileAuditSink {
+    path: PathBuf,
+    max_bytes: u64,
+    max_files: u32,
+    file: Mutex<File>,
+}
+
+impl FileAuditSink {
+    pub fn new(path: &Path, max_bytes: u64, max_files: u32) -> Result<Self> {
+        Ok(Self {
+            path: path.to_path_buf(),
+            max_bytes,
+            max_files,
+            file: Mutex::new(open(path)?),
+        })
+    }
+
+    fn rotate(&self, file: &mut File) -> Result<()> {
+        file.flush()?;
+
+        if self.max_files == 0 {
+            fs::remove_file(&self.path)?;
+        } else {
+            for index in (1..self.max_files).rev() {
+                let from = self.rotated_path(index);
+                if from.exists() {
+                    fs::rename(&from, self.rotated_path(index + 1))?;
+                }
+            }
+            fs::rename(&self.path, self.rotated_path(1))?;
+        }
+
+        *file = open(&self.path)?;
+        info!("Rotated audit log {}", self.path.display());
+        Ok(())
+    }
+
+    fn rotated_path(&self, index: u32) -> PathBuf {
+        let mut path = self.path.clone().into_os_string();
+        path.push(format!(".{}", index));
+        path.into()
+    }
+}
+
+#[async_trait]
+impl AuditSink for FileAuditSink {
+    fn name(&self) -> &'static str {
+        "file"
+    }
+
+    async fn write(&self, record: Vec<u8>) -> Result<()> {
+        let mut file = self.file.lock().unwrap();
+
+        let size = file.metadata()?.len();
+        if size > 0 && size + record.len() as u64 > self.max_bytes {
+            self.rotate(&mut file)?;
+        }
+
+        file.write_all(&record)?;
+        Ok(())
+    }
+
+    async fn flush(&self) -> Result<()> {
+        let file = self.file.lock().unwrap();
+        file.sync_data()?;
+        Ok(())
+    }
+}
+
+fn open(path: &Path) -> Result<File> {
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("Failed to open audit log {}", path.display()))
+}
@@ -0,0 +1,201 @@
+pub mod file;
+#[cfg(feature = "nats")]
+pub mod nats;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::Serialize;
+use serde_json::Value;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tracing::{info, warn};
+
+use crate::audit::file::FileAuditSink;
+#[cfg(feature = "nats")]
+use crate::audit::nats::NatsAuditSink;
+use crate::config::{AuditConfig, AuditSinkKind};
+use crate::utils::redact::redact_fields;
+
+/// Append-only destination for audit records, one JSON document per record.
+#[async_trait]
+pub trait AuditSink: Send + Sync {
+    fn name(&self) -> &'static str;
+    async fn write(&self, record: Vec<u8>) -> Result<()>;
+    async fn flush(&self) -> Result<()>;
+}
+
+// Identity of the client that made a tool call
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Caller {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_version: Option<String>,
+}
+
+impl Caller {
+    /// Reads the caller from the `_meta` argument. The transport answers `initialize`
+    /// itself, so clients repeat their `clientInfo` (and optionally a `client_id`) there.
+    pub fn from_params(params: &Value) -> Self {
+        let meta = params.get("_meta");
+        let client_info = meta.and_then(|m| m.get("clientInfo"));
+        let field = |value: Option<&Value>, key: &str| {
+            value
+                .and_then(|v| v.get(key))
+                .and_then(|v| v.as_str())
+                .map(|v| v.to_string())
+        };
+
+        Self {
+            client_id: field(meta, "client_id"),
+            client_name: field(client_info, "name"),
+            client_version: field(client_info, "version"),
+        }
+    }
+
+    /// Name used to attribute usage: the client ID, else the client name, else `anonymous`.
+    pub fn label(&self) -> String {
+        self.client_id
+            .clone()
+            .or_else(|| self.client_name.clone())
+            .unwrap_or_else(|| "anonymous".to_string())
+    }
+}
+
+// One audited tool call
+#[derive(Debug, Serialize)]
+pub struct AuditRecord {
+    /// Unix time in milliseconds at which the call started.
+    pub timestamp: u64,
+    pub caller: Caller,
+    pub tool: String,
+    /// Arguments after defaults and forced parameters were applied.
+    pub arguments: Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result_count: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+    pub latency_ms: u64,
+    pub outcome: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl AuditRecord {
+    pub fn new(tool: &str, caller: Caller, arguments: Value) -> Self {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or_default();
+
+        Self {
+            timestamp,
+            caller,
+            tool: tool.to_string(),
+            arguments,
+            result_count: None,
+            request_id: None,
+            latency_ms: 0,
+            outcome: "success",
+            error: None,
+        }
+    }
+
+    pub fn completed(
+        mut self,
+        result_count: usize,
+        request_id: Option<String>,
+        elapsed: Duration,
+    ) -> Self {
+        self.result_count = Some(result_count);
+        self.request_id = request_id;
+        self.latency_ms = elapsed.as_millis() as u64;
+        self
+    }
+
+    pub fn failed(mut self, error: &anyhow::Error, elapsed: Duration) -> Self {
+        self.outcome = "error";
+        self.error = Some(error.to_string());
+        self.latency_ms = elapsed.as_millis() as u64;
+        self
+    }
+}
+
+// Audit log shared by the tools; write failures are logged and never fail the call
+#[derive(Clone, Default)]
+pub struct AuditLog {
+    sink: Option<Arc<dyn AuditSink>>,
+    redact_fields: Arc<Vec<String>>,
+}
+
+impl AuditLog {
+    pub fn new(sink: Arc<dyn AuditSink>, redact_fields: Vec<String>) -> Self {
+        Self {
+            sink: Some(sink),
+            redact_fields: Arc::new(redact_fields),
+        }
+    }
+
+    pub fn disabled() -> Self {
+        Self::default()
+    }
+
+    pub async fn from_config(config: &AuditConfig, nats_url: &str) -> Result<Self> {
+        let sink: Arc<dyn AuditSink> = match config.sink {
+            AuditSinkKind::None => return Ok(Self::disabled()),
+            AuditSinkKind::File => Arc::new(FileAuditSink::new(
+                Path::new(&config.path),
+                config.max_bytes,
+                config.max_files,
+            )?),
+            #[cfg(not(feature = "nats"))]
+            AuditSinkKind::Nats => {
+                let _ = nats_url;
+                anyhow::bail!(
+                    "NATS audit sink is not available in this build (enable the `nats` feature)"
+                )
+            }
+            #[cfg(feature = "nats")]
+            AuditSinkKind::Nats => {
+                let client = async_nats::connect(nats_url).await?;
+                Arc::new(NatsAuditSink::new(client, &config.subject))
+            }
+        };
+
+        info!("Writing audit log to {} sink", sink.name());
+        Ok(Self::new(sink, config.redact_fields.clone()))
+    }
+
+    pub async fn record(&self, record: AuditRecord) {
+        let Some(sink) = self.sink.as_ref() else {
+            return;
+        };
+
+        let mut value = match serde_json::to_value(&record) {
+            Ok(value) => value,
+            Err(e) => {
+                warn!("Failed to serialize audit record: {}", e);
+                return;
+            }
+        };
+        redact_fields(&mut value, &self.redact_fields);
+
+        let mut bytes = value.to_string().into_bytes();
+        bytes.push(b'\n');
+        if let Err(e) = sink.write(bytes).await {
+            warn!("Failed to write audit record to {}: {}", sink.name(), e);
+        }
+    }
+
+    /// Makes sure every record written so far has reached the sink.
+    pub async fn flush(&self) {
+        if let Some(sink) = self.sink.as_ref()
+            && let Err(e) = sink.flush().await
+        {
+            warn!("Failed to flush audit log to {}: {}", sink.name(), e);
+        }
+    }
+}
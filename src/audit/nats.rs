@@ -0,0 +1,39 @@
+use anyhow::Result;
+use async_nats::Client;
+use async_trait::async_trait;
+
+use crate::audit::AuditSink;
+
+// Publishes each audit record to a NATS subject, e.g. one captured by a JetStream stream
+pub struct NatsAuditSink {
+    client: Client,
+    subject: String,
+}
+
+impl NatsAuditSink {
+    pub fn new(client: Client, subject: &str) -> Self {
+        Self {
+            client,
+            subject: subject.to_string(),
+        }
+    }
+}
+
+#[async_trait]
+impl AuditSink for NatsAuditSink {
+    fn name(&self) -> &'static str {
+        "nats"
+    }
+
+    async fn write(&self, record: Vec<u8>) -> Result<()> {
+        self.client
+            .publish(self.subject.clone(), record.into())
+            .await?;
+        Ok(())
+    }
+
+    async fn flush(&self) -> Result<()> {
+        self.client.flush().await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,376 @@
+pub mod policy;
+
+#[cfg(feature = "jwt")]
+use anyhow::Context;
+use anyhow::Result;
+#[cfg(feature = "jwt")]
+use jsonwebtoken::{Algorithm, DecodingKey, Validation};
+#[cfg(feature = "jwt")]
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::HashMap;
+#[cfg(feature = "jwt")]
+use std::fs;
+use std::sync::{Arc, Mutex};
+use subtle::ConstantTimeEq;
+use tracing::{debug, info, warn};
+
+use crate::auth::policy::{ClientPolicy, Policy};
+use crate::config::{AuthConfig, AuthMode};
+use crate::metrics::metrics;
+use crate::utils::rate_limiter::RateLimiter;
+
+/// Field of the `_meta` argument that carries the caller's credentials.
+pub const AUTHORIZATION_FIELD: &str = "authorization";
+
+#[cfg(feature = "jwt")]
+#[derive(Deserialize)]
+struct Claims {
+    sub: String,
+}
+
+// Authenticates callers by shared token or JWT and enforces their policy
+pub struct Authenticator {
+    policy: Arc<Policy>,
+    #[cfg(feature = "jwt")]
+    jwt: Option<(DecodingKey, Validation)>,
+    rate_limiters: Mutex<HashMap<String, Arc<RateLimiter>>>,
+}
+
+impl Authenticator {
+    pub fn new(config: &AuthConfig, policy: Arc<Policy>) -> Result<Self> {
+        #[cfg(feature = "jwt")]
+        let jwt = match config.mode {
+            AuthMode::Jwt => Some(jwt_verifier(config)?),
+            _ => None,
+        };
+        #[cfg(not(feature = "jwt"))]
+        if config.mode == AuthMode::Jwt {
+            return Err(anyhow::anyhow!(
+                "auth.mode = \"jwt\" needs JWT support, which this build excludes (enable the `jwt` feature)"
+            ));
+        }
+
+        info!(
+            "Client authentication enabled ({:?}) for {} client(s)",
+            config.mode,
+            policy.clients.len()
+        );
+        Ok(Self {
+            policy,
+            #[cfg(feature = "jwt")]
+            jwt,
+            rate_limiters: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Verifies the `Bearer` credential and returns the client ID with its policy.
+    pub fn authenticate(&self, authorization: Option<&str>) -> Result<(String, &ClientPolicy)> {
+        let credential = authorization
+            .map(|value| value.trim())
+            .map(|value| value.strip_prefix("Bearer ").unwrap_or(value).trim())
+            .filter(|value| !value.is_empty());
+
+        let authenticated = match credential {
+            Some(credential) => self.verify(credential),
+            None => Err(anyhow::anyhow!(
+                "Missing credentials in the Authorization header or _meta.{}",
+                AUTHORIZATION_FIELD
+            )),
+        };
+
+        if let Err(e) = &authenticated {
+            warn!("Rejected call: {}", e);
+            metrics().record_auth_rejection("unauthenticated");
+        }
+        authenticated
+    }
+
+    fn verify(&self, credential: &str) -> Result<(String, &ClientPolicy)> {
+        #[cfg(feature = "jwt")]
+        if let Some((key, validation)) = &self.jwt {
+            let token = jsonwebtoken::decode::<Claims>(credential, key, validation)
+                .map_err(|e| anyhow::anyhow!("Invalid token: {}", e))?;
+            let client_id = token.claims.sub;
+            let policy = self.policy.client(&client_id).ok_or_else(|| {
+                anyhow::anyhow!("Client {} is not allowed by the policy", client_id)
+            })?;
+            return Ok((client_id, policy));
+        }
+
+        // Compared in constant time so response times don't reveal how much of a token matched
+        self.policy
+            .clients
+            .iter()
+            .find(|(_, policy)| {
+                policy
+                    .token
+                    .as_ref()
+                    .is_some_and(|token| token.as_bytes().ct_eq(credential.as_bytes()).into())
+            })
+            .map(|(client_id, policy)| (client_id.clone(), policy))
+            .ok_or_else(|| anyhow::anyhow!("Invalid token"))
+    }
+
+    /// Checks the tool and the rate limit against the client's policy. Arguments
+    /// are checked by the tools with [`Authenticator::check_parameters`] once
+    /// defaults and forced values have been applied.
+    pub async fn authorize(
+        &self,
+        client_id: &str,
+        policy: &ClientPolicy,
+        tool: &str,
+    ) -> Result<()> {
+        if let Some(tools) = &policy.tools
+            && !tools.iter().any(|allowed| allowed == tool)
+        {
+            return Err(self.forbidden(format!("Client {} may not call {}", client_id, tool)));
+        }
+
+        if let Some(limiter) = self.rate_limiter(client_id, policy)
+            && !limiter.try_acquire().await
+        {
+            warn!("Rate limit exceeded for client {}", client_id);
+            metrics().record_auth_rejection("rate_limited");
+            return Err(anyhow::anyhow!(
+                "Rate limit of {} requests per minute exceeded for client {}",
+                policy.requests_per_minute.unwrap_or_default(),
+                client_id
+            ));
+        }
+
+        debug!("Authorized {} for client {}", tool, client_id);
+        Ok(())
+    }
+
+    /// Checks the arguments a tool is about to send to Tavily against the allowed
+    /// values in the client's policy. `client_id` is the identity verified by
+    /// [`Authenticator::authenticate`]; calls without one are rejected.
+    pub fn check_parameters(&self, client_id: Option<&str>, params: &Value) -> Result<()> {
+        let Some(client_id) = client_id else {
+            return Err(self.forbidden("Missing authenticated client".to_string()));
+        };
+        let Some(policy) = self.policy.client(client_id) else {
+            return Err(
+                self.forbidden(format!("Client {} is not allowed by the policy", client_id))
+            );
+        };
+
+        for (name, allowed) in &policy.parameters {
+            if let Some(value) = params.get(name)
+                && !allowed.contains(value)
+            {
+                return Err(self.forbidden(format!(
+                    "Client {} may not use {} = {}",
+                    client_id, name, value
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    fn forbidden(&self, message: String) -> anyhow::Error {
+        warn!("Rejected call: {}", message);
+        metrics().record_auth_rejection("forbidden");
+        anyhow::anyhow!(message)
+    }
+
+    // Limiters are created on first use so that JWT subjects under the default policy get their own
+    fn rate_limiter(&self, client_id: &str, policy: &ClientPolicy) -> Option<Arc<RateLimiter>> {
+        let per_minute = policy.requests_per_minute.filter(|rpm| *rpm > 0)?;
+        let mut limiters = self.rate_limiters.lock().unwrap();
+        let limiter = limiters.entry(client_id.to_string()).or_insert_with(|| {
+            Arc::new(RateLimiter::new(f64::from(per_minute) / 60.0).with_burst(per_minute))
+        });
+        Some(limiter.clone())
+    }
+}
+
+#[cfg(feature = "jwt")]
+fn jwt_verifier(config: &AuthConfig) -> Result<(DecodingKey, Validation)> {
+    let (key, algorithm) = match (&config.jwt_secret, &config.jwt_public_key_file) {
+        (_, Some(path)) => {
+            let pem = fs::read(path)
+                .with_context(|| format!("Failed to read JWT public key {}", path))?;
+            (
+                DecodingKey::from_rsa_pem(&pem).context("Invalid JWT public key")?,
+                Algorithm::RS256,
+            )
+        }
+        (Some(secret), None) => (
+            DecodingKey::from_secret(secret.as_bytes()),
+            Algorithm::HS256,
+        ),
+        (None, None) => {
+            return Err(anyhow::anyhow!(
+                "auth.mode = \"jwt\" needs auth.jwt_secret or auth.jwt_public_key_file"
+            ));
+        }
+    };
+
+    let mut validation = Validation::new(algorithm);
+    match &config.jwt_audience {
+        Some(audience) => validation.set_audience(&[audience]),
+        None => validation.validate_aud = false,
+    }
+    if let Some(issuer) = &config.jwt_issuer {
+        validation.set_issuer(&[issuer]);
+    }
+    Ok((key, validation))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "jwt")]
+    use crate::utils::time::unix_millis;
+    #[cfg(feature = "jwt")]
+    use jsonwebtoken::{EncodingKey, Header};
+    use serde_json::json;
+
+    const SECRET: &str = "jwt-secret";
+
+    fn policy() -> Arc<Policy> {
+        let mut policy = Policy::default();
+        policy.clients.insert(
+            "agent".to_string(),
+            ClientPolicy {
+                token: Some("agent-token".to_string()),
+                tools: Some(vec!["tavily-search".to_string()]),
+                parameters: [("search_depth".to_string(), vec![json!("basic")])].into(),
+                requests_per_minute: Some(2),
+                ..ClientPolicy::default()
+            },
+        );
+        Arc::new(policy)
+    }
+
+    fn authenticator(mode: AuthMode) -> Authenticator {
+        let config = AuthConfig {
+            mode,
+            jwt_secret: Some(SECRET.to_string()),
+            ..AuthConfig::default()
+        };
+        Authenticator::new(&config, policy()).unwrap()
+    }
+
+    #[cfg(feature = "jwt")]
+    fn jwt(sub: &str, secret: &str) -> String {
+        let claims = json!({ "sub": sub, "exp": unix_millis() / 1000 + 60 });
+        jsonwebtoken::encode(
+            &Header::default(),
+            &claims,
+            &EncodingKey::from_secret(secret.as_bytes()),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn tokens_identify_their_client() {
+        let auth = authenticator(AuthMode::Token);
+
+        for credential in ["agent-token", "Bearer agent-token", " Bearer  agent-token "] {
+            let (client_id, _) = auth.authenticate(Some(credential)).unwrap();
+            assert_eq!(client_id, "agent");
+        }
+    }
+
+    #[test]
+    fn missing_and_unknown_credentials_are_rejected() {
+        let auth = authenticator(AuthMode::Token);
+
+        for credential in [None, Some(""), Some("  ")] {
+            let error = auth.authenticate(credential).unwrap_err().to_string();
+            assert!(error.starts_with("Missing credentials"), "{}", error);
+        }
+        let error = auth.authenticate(Some("other")).unwrap_err().to_string();
+        assert_eq!(error, "Invalid token");
+    }
+
+    #[cfg(feature = "jwt")]
+    #[test]
+    fn jwt_subjects_are_looked_up_in_the_policy() {
+        let auth = authenticator(AuthMode::Jwt);
+
+        let (client_id, _) = auth.authenticate(Some(&jwt("agent", SECRET))).unwrap();
+        assert_eq!(client_id, "agent");
+
+        let error = auth
+            .authenticate(Some(&jwt("stranger", SECRET)))
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("not allowed by the policy"), "{}", error);
+        let error = auth
+            .authenticate(Some(&jwt("agent", "other-secret")))
+            .unwrap_err()
+            .to_string();
+        assert!(error.starts_with("Invalid token"), "{}", error);
+    }
+
+    #[cfg(feature = "jwt")]
+    #[test]
+    fn jwt_mode_needs_a_key() {
+        let config = AuthConfig {
+            mode: AuthMode::Jwt,
+            ..AuthConfig::default()
+        };
+        assert!(Authenticator::new(&config, policy()).is_err());
+    }
+
+    #[tokio::test]
+    async fn only_listed_tools_are_authorized() {
+        let auth = authenticator(AuthMode::Token);
+        let policy = auth.policy.clone();
+        let policy = policy.client("agent").unwrap();
+
+        auth.authorize("agent", policy, "tavily-search")
+            .await
+            .unwrap();
+        let error = auth
+            .authorize("agent", policy, "tavily-extract")
+            .await
+            .unwrap_err()
+            .to_string();
+        assert_eq!(error, "Client agent may not call tavily-extract");
+    }
+
+    #[tokio::test]
+    async fn clients_are_rate_limited_per_minute() {
+        let auth = authenticator(AuthMode::Token);
+        let policy = auth.policy.clone();
+        let policy = policy.client("agent").unwrap();
+
+        for _ in 0..2 {
+            auth.authorize("agent", policy, "tavily-search")
+                .await
+                .unwrap();
+        }
+        let error = auth
+            .authorize("agent", policy, "tavily-search")
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(error.starts_with("Rate limit of 2 requests"), "{}", error);
+    }
+
+    #[test]
+    fn parameters_are_checked_against_allowed_values() {
+        let auth = authenticator(AuthMode::Token);
+
+        auth.check_parameters(Some("agent"), &json!({ "search_depth": "basic" }))
+            .unwrap();
+        auth.check_parameters(Some("agent"), &json!({ "query": "rust" }))
+            .unwrap();
+
+        let error = auth
+            .check_parameters(Some("agent"), &json!({ "search_depth": "advanced" }))
+            .unwrap_err()
+            .to_string();
+        assert_eq!(
+            error,
+            "Client agent may not use search_depth = \"advanced\""
+        );
+        assert!(auth.check_parameters(None, &json!({})).is_err());
+        assert!(auth.check_parameters(Some("stranger"), &json!({})).is_err());
+    }
+}
@@ -0,0 +1,75 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use crate::config::BudgetConfig;
+
+// Which clients may call which tools, with what arguments and how often
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Policy {
+    /// Applies to JWT subjects that have no entry of their own.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default: Option<ClientPolicy>,
+    pub clients: HashMap<String, ClientPolicy>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ClientPolicy {
+    /// Shared token the client presents in token mode.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+    /// Tools the client may call; all tools when unset.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tools: Option<Vec<String>>,
+    /// Allowed values per argument, e.g. `search_depth = ["basic"]`.
+    pub parameters: HashMap<String, Vec<Value>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub requests_per_minute: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub daily_credits: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub monthly_credits: Option<u64>,
+    /// Lets the client see every client's credits in the usage report.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub admin: bool,
+}
+
+impl Policy {
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read policy file {}", path.display()))?;
+        toml::from_str(&contents)
+            .with_context(|| format!("Failed to parse policy file {}", path.display()))
+    }
+
+    pub fn client(&self, client_id: &str) -> Option<&ClientPolicy> {
+        self.clients.get(client_id).or(self.default.as_ref())
+    }
+
+    /// Per-client credit budget, if the client's policy sets one.
+    pub fn budget(&self, client_id: &str) -> Option<BudgetConfig> {
+        let policy = self.client(client_id)?;
+        if policy.daily_credits.is_none() && policy.monthly_credits.is_none() {
+            return None;
+        }
+
+        Some(BudgetConfig {
+            daily_credits: policy.daily_credits,
+            monthly_credits: policy.monthly_credits,
+            ..BudgetConfig::default()
+        })
+    }
+
+    /// Whether any client, or the default policy, has a credit budget.
+    pub fn has_budgets(&self) -> bool {
+        self.clients
+            .values()
+            .chain(&self.default)
+            .any(|p| p.daily_credits.is_some() || p.monthly_credits.is_some())
+    }
+}
@@ -0,0 +1,107 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use super::CacheBackend;
+use crate::config::CacheConfig;
+
+// In-process cache, local to a single server instance
+pub struct MemoryCache {
+    retention: Duration,
+    max_entries: usize,
+    entries: Mutex<HashMap<String, (Instant, Vec<u8>)>>,
+}
+
+impl MemoryCache {
+    pub fn new(retention: Duration, max_entries: usize) -> Self {
+        Self {
+            retention,
+            max_entries: max_entries.max(1),
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn from_config(config: &CacheConfig) -> Self {
+        Self::new(config.retention(), config.max_entries)
+    }
+}
+
+#[async_trait]
+impl CacheBackend for MemoryCache {
+    fn name(&self) -> &'static str {
+        "memory"
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        let mut entries = self.entries.lock().unwrap();
+
+        match entries.get(key) {
+            Some((stored_at, value)) if stored_at.elapsed() < self.retention => {
+                Ok(Some(value.clone()))
+            }
+            Some(_) => {
+                entries.remove(key);
+                Ok(None)
+            }
+            None => Ok(None),
+        }
+    }
+
+    async fn put(&self, key: &str, value: Vec<u8>) -> Result<()> {
+        let mut entries = self.entries.lock().unwrap();
+        let retention = self.retention;
+        entries.retain(|_, (stored_at, _)| stored_at.elapsed() < retention);
+
+        // Make room by dropping the oldest entry
+        if entries.len() >= self.max_entries && !entries.contains_key(key) {
+            let oldest = entries
+                .iter()
+                .min_by_key(|(_, (stored_at, _))| *stored_at)
+                .map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                entries.remove(&oldest);
+            }
+        }
+
+        entries.insert(key.to_string(), (Instant::now(), value));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn evicts_the_oldest_entry_at_capacity() {
+        let cache = MemoryCache::new(Duration::from_secs(60), 2);
+        cache.put("a", b"1".to_vec()).await.unwrap();
+        cache.put("b", b"2".to_vec()).await.unwrap();
+        cache.put("c", b"3".to_vec()).await.unwrap();
+
+        assert_eq!(cache.get("a").await.unwrap(), None);
+        assert_eq!(cache.get("b").await.unwrap(), Some(b"2".to_vec()));
+        assert_eq!(cache.get("c").await.unwrap(), Some(b"3".to_vec()));
+    }
+
+    #[tokio::test]
+    async fn replacing_an_entry_does_not_evict_another() {
+        let cache = MemoryCache::new(Duration::from_secs(60), 2);
+        cache.put("a", b"1".to_vec()).await.unwrap();
+        cache.put("b", b"2".to_vec()).await.unwrap();
+        cache.put("b", b"3".to_vec()).await.unwrap();
+
+        assert_eq!(cache.get("a").await.unwrap(), Some(b"1".to_vec()));
+        assert_eq!(cache.get("b").await.unwrap(), Some(b"3".to_vec()));
+    }
+
+    #[tokio::test]
+    async fn drops_entries_past_retention() {
+        let cache = MemoryCache::new(Duration::ZERO, 10);
+        cache.put("a", b"1".to_vec()).await.unwrap();
+
+        assert_eq!(cache.get("a").await.unwrap(), None);
+    }
+}
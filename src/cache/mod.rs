@@ -0,0 +1,365 @@
+pub mod memory;
+#[cfg(feature = "nats")]
+pub mod nats_kv;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tracing::{debug, info, warn};
+
+use crate::cache::memory::MemoryCache;
+#[cfg(feature = "nats")]
+use crate::cache::nats_kv::NatsKvCache;
+use crate::config::{CacheBackendKind, CacheConfig};
+use crate::metrics::metrics;
+use crate::utils::time::unix_millis;
+
+/// Storage for serialized Tavily responses.
+#[async_trait]
+pub trait CacheBackend: Send + Sync {
+    fn name(&self) -> &'static str;
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>>;
+    async fn put(&self, key: &str, value: Vec<u8>) -> Result<()>;
+
+    /// Pushes out writes that have not reached the backend yet.
+    async fn flush(&self) -> Result<()> {
+        Ok(())
+    }
+
+    /// Fails if the backend cannot currently serve requests.
+    async fn health(&self) -> Result<()> {
+        Ok(())
+    }
+}
+
+// Response cache shared by the tools; backend errors are treated as cache misses.
+// Entries are kept for a grace period after they expire so they can stand in for
+// Tavily while it is failing.
+#[derive(Clone, Default)]
+pub struct Cache {
+    backend: Option<Arc<dyn CacheBackend>>,
+    ttl: Duration,
+    stale_grace: Duration,
+    revalidate_interval: Duration,
+    refreshing: Arc<Mutex<HashSet<String>>>,
+}
+
+// What the backends store: the response and when it was fetched
+#[derive(Serialize, Deserialize)]
+struct Entry<T> {
+    fetched_at: u64,
+    value: T,
+}
+
+/// An expired response served in place of a failed upstream call.
+pub struct Stale<T> {
+    pub value: T,
+    /// Unix time in milliseconds when the response was fetched from Tavily.
+    pub fetched_at: u64,
+}
+
+impl Cache {
+    pub fn new(backend: Arc<dyn CacheBackend>, config: &CacheConfig) -> Self {
+        Self {
+            backend: Some(backend),
+            ttl: config.ttl(),
+            stale_grace: config.stale_grace(),
+            revalidate_interval: config.revalidate_interval(),
+            refreshing: Arc::default(),
+        }
+    }
+
+    pub fn disabled() -> Self {
+        Self::default()
+    }
+
+    /// Builds the configured backend, falling back to memory if JetStream is
+    /// unavailable. The `nats` backend uses the caller's connection.
+    pub async fn from_config(
+        config: &CacheConfig,
+        #[cfg(feature = "nats")] nats: Option<&async_nats::Client>,
+    ) -> Self {
+        match config.backend {
+            CacheBackendKind::None => {
+                info!("Response cache disabled");
+                Self::disabled()
+            }
+            CacheBackendKind::Memory => {
+                Self::new(Arc::new(MemoryCache::from_config(config)), config)
+            }
+            #[cfg(not(feature = "nats"))]
+            CacheBackendKind::Nats => {
+                warn!("NATS KV cache is not available in this build, using in-memory cache");
+                Self::new(Arc::new(MemoryCache::from_config(config)), config)
+            }
+            #[cfg(feature = "nats")]
+            CacheBackendKind::Nats => {
+                let kv_cache = match nats {
+                    Some(client) => {
+                        NatsKvCache::new(client.clone(), &config.bucket, config.retention()).await
+                    }
+                    None => Err(anyhow::anyhow!("no NATS connection")),
+                };
+
+                match kv_cache {
+                    Ok(kv_cache) => Self::new(Arc::new(kv_cache), config),
+                    Err(e) => {
+                        warn!(
+                            "NATS KV cache unavailable, falling back to in-memory cache: {}",
+                            e
+                        );
+                        Self::new(Arc::new(MemoryCache::from_config(config)), config)
+                    }
+                }
+            }
+        }
+    }
+
+    /// Builds a key from the endpoint and the SHA-256 of the request
+    /// parameters. Object keys are sorted first, so equal requests map to the
+    /// same key on every replica, and no caller can craft parameters that
+    /// collide with another caller's request.
+    pub fn key(endpoint: &str, params: &Value) -> String {
+        let mut canonical = String::new();
+        write_canonical(params, &mut canonical);
+        let digest = Sha256::digest(canonical.as_bytes());
+        let hex: String = digest.iter().map(|byte| format!("{:02x}", byte)).collect();
+        format!("{}.{}", endpoint, hex)
+    }
+
+    /// Returns the cached response if it is younger than the TTL.
+    pub async fn get<T: DeserializeOwned>(&self, key: &str) -> Option<T> {
+        let backend = self.backend.as_ref()?;
+
+        let entry = self
+            .lookup::<T>(backend.as_ref(), key)
+            .await
+            .filter(|entry| age(entry.fetched_at) < self.ttl);
+        match entry {
+            Some(entry) => {
+                debug!("Cache hit for {} ({})", key, backend.name());
+                metrics().record_cache(backend.name(), "hit");
+                Some(entry.value)
+            }
+            None => {
+                debug!("Cache miss for {} ({})", key, backend.name());
+                metrics().record_cache(backend.name(), "miss");
+                None
+            }
+        }
+    }
+
+    /// Returns the cached response even if it has expired, as long as it is
+    /// still within the stale grace period.
+    pub async fn get_stale<T: DeserializeOwned>(&self, key: &str) -> Option<Stale<T>> {
+        let backend = self.backend.as_ref()?;
+
+        let entry = self
+            .lookup::<T>(backend.as_ref(), key)
+            .await
+            .filter(|entry| age(entry.fetched_at) < self.ttl + self.stale_grace)?;
+        debug!("Serving stale cache entry {} ({})", key, backend.name());
+        metrics().record_cache(backend.name(), "stale");
+        Some(Stale {
+            value: entry.value,
+            fetched_at: entry.fetched_at,
+        })
+    }
+
+    async fn lookup<T: DeserializeOwned>(
+        &self,
+        backend: &dyn CacheBackend,
+        key: &str,
+    ) -> Option<Entry<T>> {
+        match backend.get(key).await {
+            Ok(Some(bytes)) => match serde_json::from_slice(&bytes) {
+                Ok(entry) => Some(entry),
+                Err(e) => {
+                    warn!("Ignoring unreadable cache entry {}: {}", key, e);
+                    None
+                }
+            },
+            Ok(None) => None,
+            Err(e) => {
+                warn!("Cache lookup failed for {}: {}", key, e);
+                None
+            }
+        }
+    }
+
+    pub async fn put<T: Serialize>(&self, key: &str, value: &T) {
+        let Some(backend) = self.backend.as_ref() else {
+            return;
+        };
+
+        let entry = Entry {
+            fetched_at: unix_millis(),
+            value,
+        };
+        let bytes = match serde_json::to_vec(&entry) {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                warn!("Failed to serialize cache entry {}: {}", key, e);
+                return;
+            }
+        };
+
+        if let Err(e) = backend.put(key, bytes).await {
+            warn!("Failed to store cache entry {}: {}", key, e);
+        }
+    }
+
+    /// Refreshes a stale entry in the background, calling `fetch` every
+    /// `revalidate_interval` until it succeeds or the entry leaves the grace
+    /// period. Only one refresh runs per key.
+    pub fn revalidate<T, F, Fut>(&self, key: &str, fetched_at: u64, fetch: F)
+    where
+        T: Serialize + Send + Sync + 'static,
+        F: Fn() -> Fut + Send + 'static,
+        Fut: Future<Output = Result<T>> + Send + 'static,
+    {
+        if self.backend.is_none() || !self.refreshing.lock().unwrap().insert(key.to_string()) {
+            return;
+        }
+
+        let cache = self.clone();
+        let key = key.to_string();
+        let expires = self.ttl + self.stale_grace;
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(cache.revalidate_interval).await;
+                if age(fetched_at) >= expires {
+                    debug!("Stopped refreshing {}, entry is past the grace period", key);
+                    break;
+                }
+
+                match fetch().await {
+                    Ok(value) => {
+                        cache.put(&key, &value).await;
+                        info!("Refreshed stale cache entry {}", key);
+                        break;
+                    }
+                    Err(e) => debug!("Refreshing {} failed: {}", key, e),
+                }
+            }
+            cache.refreshing.lock().unwrap().remove(&key);
+        });
+    }
+
+    /// Probes the backend; `None` when caching is disabled.
+    pub async fn check(&self) -> Option<(&'static str, Result<()>)> {
+        let backend = self.backend.as_ref()?;
+        Some((backend.name(), backend.health().await))
+    }
+
+    pub async fn flush(&self) {
+        if let Some(backend) = self.backend.as_ref()
+            && let Err(e) = backend.flush().await
+        {
+            warn!("Failed to flush {} cache: {}", backend.name(), e);
+        }
+    }
+}
+
+fn age(fetched_at: u64) -> Duration {
+    Duration::from_millis(unix_millis().saturating_sub(fetched_at))
+}
+
+// Compact JSON with object keys in sorted order, whatever order the map keeps them in
+fn write_canonical(value: &Value, out: &mut String) {
+    match value {
+        Value::Object(map) => {
+            let mut entries: Vec<_> = map.iter().collect();
+            entries.sort_by_key(|(key, _)| *key);
+
+            out.push('{');
+            for (index, (key, value)) in entries.into_iter().enumerate() {
+                if index > 0 {
+                    out.push(',');
+                }
+                out.push_str(&Value::String(key.clone()).to_string());
+                out.push(':');
+                write_canonical(value, out);
+            }
+            out.push('}');
+        }
+        Value::Array(items) => {
+            out.push('[');
+            for (index, item) in items.iter().enumerate() {
+                if index > 0 {
+                    out.push(',');
+                }
+                write_canonical(item, out);
+            }
+            out.push(']');
+        }
+        scalar => out.push_str(&scalar.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{Map, json};
+
+    #[test]
+    fn keys_ignore_the_order_of_object_keys() {
+        let mut forward = Map::new();
+        forward.insert("query".to_string(), json!("rust"));
+        forward.insert("max_results".to_string(), json!(5));
+        forward.insert("include_domains".to_string(), json!([{ "b": 1, "a": 2 }]));
+        let mut backward = Map::new();
+        backward.insert("include_domains".to_string(), json!([{ "a": 2, "b": 1 }]));
+        backward.insert("max_results".to_string(), json!(5));
+        backward.insert("query".to_string(), json!("rust"));
+
+        assert_eq!(
+            Cache::key("search", &Value::Object(forward)),
+            Cache::key("search", &Value::Object(backward))
+        );
+    }
+
+    #[test]
+    fn keys_differ_by_endpoint_and_parameters() {
+        let params = json!({ "query": "rust" });
+
+        assert_ne!(
+            Cache::key("search", &params),
+            Cache::key("extract", &params)
+        );
+        assert_ne!(
+            Cache::key("search", &params),
+            Cache::key("search", &json!({ "query": "rust", "max_results": 5 }))
+        );
+        assert_ne!(
+            Cache::key("search", &json!({ "urls": ["a", "b"] })),
+            Cache::key("search", &json!({ "urls": ["b", "a"] }))
+        );
+    }
+
+    #[test]
+    fn keys_are_valid_kv_keys() {
+        let key = Cache::key("search", &json!({ "query": "rust" }));
+        let (endpoint, hash) = key.split_once('.').unwrap();
+        assert_eq!(endpoint, "search");
+        assert_eq!(hash.len(), 64);
+        assert!(hash.chars().all(|c| c.is_ascii_hexdigit()));
+    }
+
+    #[test]
+    fn canonical_form_sorts_nested_keys() {
+        let mut canonical = String::new();
+        write_canonical(
+            &json!({ "b": [1, { "d": null, "c": "x" }], "a": true }),
+            &mut canonical,
+        );
+        assert_eq!(canonical, r#"{"a":true,"b":[1,{"c":"x","d":null}]}"#);
+    }
+}
@@ -0,0 +1,105 @@
+use anyhow::{Context, Result};
+use async_nats::jetstream::{self, kv};
+use async_trait::async_trait;
+use std::time::Duration;
+use tracing::info;
+
+use super::CacheBackend;
+
+// Cache stored in a JetStream KV bucket so every replica shares the same entries
+pub struct NatsKvCache {
+    client: async_nats::Client,
+    store: kv::Store,
+}
+
+impl NatsKvCache {
+    /// Opens the bucket, creating it with `retention` as the entry max age if
+    /// needed. An existing bucket with a different max age is updated, failing
+    /// if the server refuses.
+    pub async fn new(
+        client: async_nats::Client,
+        bucket: &str,
+        retention: Duration,
+    ) -> Result<Self> {
+        let jetstream = jetstream::new(client.clone());
+
+        let store = match jetstream.get_key_value(bucket).await {
+            Ok(store) => {
+                update_max_age(&jetstream, &store, retention).await?;
+                store
+            }
+            Err(_) => jetstream
+                .create_key_value(kv::Config {
+                    bucket: bucket.to_string(),
+                    description: "Tavily MCP response cache".to_string(),
+                    history: 1,
+                    max_age: retention,
+                    ..Default::default()
+                })
+                .await
+                .with_context(|| format!("Failed to create cache bucket {}", bucket))?,
+        };
+
+        info!("Using NATS KV cache bucket {}", bucket);
+        Ok(Self { client, store })
+    }
+}
+
+// Entries must outlive the stale grace period, which a bucket created with an
+// older configuration may not allow
+async fn update_max_age(
+    jetstream: &jetstream::Context,
+    store: &kv::Store,
+    retention: Duration,
+) -> Result<()> {
+    let mut stream = store.stream.clone();
+    let mut config = stream
+        .info()
+        .await
+        .with_context(|| format!("Failed to read cache bucket {}", store.name))?
+        .config
+        .clone();
+    if config.max_age == retention {
+        return Ok(());
+    }
+
+    info!(
+        "Changing max age of cache bucket {} from {:?} to {:?}",
+        store.name, config.max_age, retention
+    );
+    config.max_age = retention;
+    jetstream.update_stream(&config).await.with_context(|| {
+        format!(
+            "Failed to change max age of cache bucket {} to {:?}",
+            store.name, retention
+        )
+    })?;
+    Ok(())
+}
+
+#[async_trait]
+impl CacheBackend for NatsKvCache {
+    fn name(&self) -> &'static str {
+        "nats-kv"
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        let value = self.store.get(key).await?;
+        Ok(value.map(|bytes| bytes.to_vec()))
+    }
+
+    async fn put(&self, key: &str, value: Vec<u8>) -> Result<()> {
+        self.store.put(key, value.into()).await?;
+        Ok(())
+    }
+
+    async fn flush(&self) -> Result<()> {
+        self.client.flush().await?;
+        Ok(())
+    }
+
+    async fn health(&self) -> Result<()> {
+        self.store.status().await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,54 @@
+use clap::{Parser, Subcommand};
+use std::path::PathBuf;
+
+/// Tavily search and extract tools served over the Anet MCP protocol.
+#[derive(Debug, Parser)]
+#[command(name = "tavily-anet-mcp", version, about)]
+pub struct Cli {
+    /// Path to a TOML configuration file (also read from MCP_CONFIG)
+    #[arg(long, global = true, value_name = "PATH")]
+    pub config: Option<PathBuf>,
+
+    /// Print the effective configuration with secrets redacted and exit
+    #[arg(long, global = true)]
+    pub print_config: bool,
+
+    /// NATS server URL, overrides NATS_URL and the config file
+    #[arg(long, global = true, value_name = "URL")]
+    pub nats_url: Option<String>,
+
+    /// Subject to listen on for MCP requests, overrides MCP_SUBJECT and the config file
+    #[arg(long, global = true, value_name = "SUBJECT")]
+    pub subject: Option<String>,
+
+    /// Log filter such as `info` or `tavily_anet_mcp=debug`, overrides RUST_LOG and the config file
+    #[arg(long, global = true, value_name = "FILTER")]
+    pub log_level: Option<String>,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Run the MCP server (the default when no subcommand is given)
+    Serve,
+    /// Validate the configuration and check the API key against Tavily
+    Check {
+        /// Only validate the configuration, without calling Tavily
+        #[arg(long)]
+        offline: bool,
+    },
+    /// Print the name, description and input schema of every enabled tool
+    ListTools,
+    /// Print the API key and plan usage reported by Tavily
+    Usage,
+    /// Invoke a single tool and print its output
+    Call {
+        /// Tool name, e.g. `tavily-search`
+        tool: String,
+        /// Tool arguments as a JSON object
+        #[arg(long, value_name = "JSON", default_value = "{}")]
+        args: String,
+    },
+}
@@ -0,0 +1,310 @@
+use anyhow::{Context, Result};
+use reqwest::{Client, Method, StatusCode};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+use tracing::field::Empty;
+use tracing::{Instrument, debug, error, info_span, warn};
+
+use crate::config::{
+    CircuitBreakerConfig, ClientKeyPolicy, RateLimitConfig, RetryConfig, TavilyConfig,
+};
+use crate::keys::{ApiKey, KeyPool, take_client_key};
+use crate::metrics::metrics;
+use crate::utils::circuit_breaker::{BreakerStatus, CircuitBreakers, UpstreamUnavailable};
+use crate::utils::credits::estimate_credits;
+use crate::utils::rate_limiter::RateLimiter;
+use crate::utils::redact::truncate_payload;
+use crate::utils::time::unix_millis;
+
+/// Tavily answered with an error status. Callers can find it with
+/// `anyhow::Error::downcast_ref`.
+#[derive(Debug)]
+pub struct UpstreamError {
+    pub status: StatusCode,
+    pub message: String,
+}
+
+impl fmt::Display for UpstreamError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Tavily API error: {}", self.message)
+    }
+}
+
+impl std::error::Error for UpstreamError {}
+
+/// Whether `error` means Tavily itself is unavailable: an open circuit breaker,
+/// a 5xx response, a timeout or a failed connection. Rejected requests and keys
+/// don't count.
+pub fn is_unavailable(error: &anyhow::Error) -> bool {
+    if error.downcast_ref::<UpstreamUnavailable>().is_some() {
+        return true;
+    }
+    if let Some(error) = error.downcast_ref::<UpstreamError>() {
+        return error.status.is_server_error();
+    }
+    error
+        .downcast_ref::<reqwest::Error>()
+        .is_some_and(|e| e.is_timeout() || e.is_connect())
+}
+
+// HTTP client for the Tavily API shared by all tools
+pub struct TavilyClient {
+    keys: KeyPool,
+    client_key_policy: ClientKeyPolicy,
+    last_success: AtomicU64,
+    base_url: String,
+    client: Client,
+    retry: RetryConfig,
+    rate_limiter: Option<RateLimiter>,
+    breakers: CircuitBreakers,
+}
+
+impl TavilyClient {
+    pub fn new(
+        keys: Vec<ApiKey>,
+        tavily: &TavilyConfig,
+        retry: &RetryConfig,
+        rate_limit: &RateLimitConfig,
+        circuit_breaker: &CircuitBreakerConfig,
+    ) -> Result<Self> {
+        let keys = KeyPool::new(
+            keys,
+            tavily.key_strategy,
+            Duration::from_secs(tavily.key_cooldown_secs),
+        );
+        debug!(
+            "Creating TavilyClient for {} with API keys: {}",
+            tavily.base_url,
+            keys.labels().join(", ")
+        );
+
+        let client = Client::builder()
+            .timeout(Duration::from_secs(tavily.timeout_secs))
+            .build()
+            .context("Failed to build HTTP client")?;
+
+        let rate_limiter = rate_limit
+            .requests_per_second
+            .filter(|rps| *rps > 0.0)
+            .map(RateLimiter::new);
+
+        Ok(Self {
+            keys,
+            client_key_policy: tavily.client_key_policy,
+            last_success: AtomicU64::new(0),
+            base_url: tavily.base_url.trim_end_matches('/').to_string(),
+            client,
+            retry: retry.clone(),
+            rate_limiter,
+            breakers: CircuitBreakers::new(circuit_breaker),
+        })
+    }
+
+    pub fn keys(&self) -> &KeyPool {
+        &self.keys
+    }
+
+    /// Unix time in milliseconds of the last successful Tavily response.
+    pub fn last_success(&self) -> Option<u64> {
+        Some(self.last_success.load(Ordering::Relaxed)).filter(|at| *at > 0)
+    }
+
+    pub fn breakers(&self) -> Vec<BreakerStatus> {
+        self.breakers.status()
+    }
+
+    /// Takes the caller's own API key out of the tool arguments, enforcing the configured policy.
+    pub fn take_client_key(&self, params: &mut Value) -> Result<Option<ApiKey>> {
+        take_client_key(params, self.client_key_policy)
+    }
+
+    /// Posts `params` to `endpoint` (e.g. `search`), retrying 429, 5xx and connection errors.
+    pub async fn post<T: DeserializeOwned>(&self, endpoint: &str, params: &Value) -> Result<T> {
+        self.send(Method::POST, endpoint, Some(params), None).await
+    }
+
+    /// Like `post`, but bills the caller's own key instead of the server's when one is given.
+    pub async fn post_with_key<T: DeserializeOwned>(
+        &self,
+        endpoint: &str,
+        params: &Value,
+        client_key: Option<&ApiKey>,
+    ) -> Result<T> {
+        self.send(Method::POST, endpoint, Some(params), client_key)
+            .await
+    }
+
+    /// Fetches `endpoint` (e.g. `usage`) with the same retry policy as `post`.
+    pub async fn get<T: DeserializeOwned>(&self, endpoint: &str) -> Result<T> {
+        self.send(Method::GET, endpoint, None, None).await
+    }
+
+    /// Like `get`, but always uses `key`, e.g. to read the usage of each configured key.
+    pub async fn get_with_key<T: DeserializeOwned>(
+        &self,
+        endpoint: &str,
+        key: &ApiKey,
+    ) -> Result<T> {
+        self.send(Method::GET, endpoint, None, Some(key)).await
+    }
+
+    async fn send<T: DeserializeOwned>(
+        &self,
+        method: Method,
+        endpoint: &str,
+        params: Option<&Value>,
+        client_key: Option<&ApiKey>,
+    ) -> Result<T> {
+        let url = format!("{}/{}", self.base_url, endpoint);
+        let mut attempt = 0;
+        let mut admitted = false;
+
+        loop {
+            // Fail fast instead of waiting out timeouts while the endpoint is down. Each
+            // attempt is admitted once, so switching keys doesn't use up half-open probes
+            if !admitted {
+                self.breakers.allow(endpoint)?;
+                admitted = true;
+            }
+
+            if let Some(rate_limiter) = &self.rate_limiter {
+                let waited = rate_limiter.acquire().await;
+                metrics().record_rate_limiter_wait(endpoint, waited);
+                if !waited.is_zero() {
+                    debug!("Rate limiter delayed {} request by {:?}", endpoint, waited);
+                }
+            }
+
+            // A caller's own key is used as is, without failover to the server's keys
+            let (key_index, key) = match client_key {
+                Some(key) => (None, key),
+                None => {
+                    let (index, key) = self
+                        .keys
+                        .select()
+                        .ok_or_else(|| anyhow::anyhow!("No Tavily API key configured"))?;
+                    (Some(index), key)
+                }
+            };
+
+            debug!(
+                "Sending request to Tavily API with key {} in Authorization header",
+                key.label
+            );
+            let span = info_span!(
+                "tavily.request",
+                otel.name = %format!("{} /{}", method, endpoint),
+                endpoint,
+                attempt,
+                key = %key.label,
+                http.status_code = Empty,
+                otel.status_code = Empty,
+            );
+            let started = Instant::now();
+            let mut request = self
+                .client
+                .request(method.clone(), &url)
+                .header("Authorization", format!("Bearer {}", key.secret));
+            if let Some(params) = params {
+                request = request.json(params);
+            }
+            let result = request.send().instrument(span.clone()).await;
+
+            match &result {
+                Ok(response) => {
+                    span.record("http.status_code", response.status().as_u16());
+                    if !response.status().is_success() {
+                        span.record("otel.status_code", "ERROR");
+                    }
+                }
+                Err(_) => {
+                    span.record("otel.status_code", "ERROR");
+                }
+            }
+
+            let failure = match result {
+                Ok(response) => {
+                    let status = response.status();
+                    debug!("Tavily API response status: {}", status);
+                    metrics().record_upstream(endpoint, status.as_str(), started.elapsed());
+                    metrics().record_key_request(&key.label, status.as_str());
+                    self.breakers.record(endpoint, status.is_server_error());
+
+                    if status.is_success() {
+                        self.last_success.store(unix_millis(), Ordering::Relaxed);
+                        if let Some(params) = params {
+                            metrics().record_credits(endpoint, estimate_credits(endpoint, params));
+                        }
+                        let response_text = response.text().await?;
+                        debug!(
+                            "Raw {} API response: {}",
+                            endpoint,
+                            truncate_payload(&response_text)
+                        );
+                        return serde_json::from_str::<T>(&response_text).with_context(|| {
+                            format!("Failed to parse Tavily {} response", endpoint)
+                        });
+                    }
+
+                    // Switch keys right away when this one is invalid, throttled or out of quota
+                    if let Some(index) = key_index
+                        && self.keys.report_failure(index, status)
+                    {
+                        metrics().record_key_failover(&key.label);
+                        continue;
+                    }
+
+                    let error_text = response.text().await.unwrap_or_default();
+                    if !is_retryable(status) || attempt >= self.retry.max_retries {
+                        error!(
+                            "Tavily API error: {} - {}",
+                            status,
+                            truncate_payload(&error_text)
+                        );
+                        return Err(UpstreamError {
+                            status,
+                            message: error_text,
+                        }
+                        .into());
+                    }
+                    format!("{} - {}", status, truncate_payload(&error_text))
+                }
+                Err(e) => {
+                    metrics().record_upstream(endpoint, "error", started.elapsed());
+                    metrics().record_key_request(&key.label, "error");
+                    self.breakers.record(endpoint, true);
+                    if attempt >= self.retry.max_retries {
+                        error!("Tavily API request failed: {}", e);
+                        return Err(e.into());
+                    }
+                    e.to_string()
+                }
+            };
+
+            let backoff = self.backoff(attempt);
+            attempt += 1;
+            admitted = false;
+            metrics().record_retry(endpoint);
+            warn!(
+                "Tavily {} request failed ({}), retry {}/{} in {:?}",
+                endpoint, failure, attempt, self.retry.max_retries, backoff
+            );
+            tokio::time::sleep(backoff).await;
+        }
+    }
+
+    fn backoff(&self, attempt: u32) -> Duration {
+        let delay = self
+            .retry
+            .initial_backoff_ms
+            .saturating_mul(1 << attempt.min(16));
+        Duration::from_millis(delay.min(self.retry.max_backoff_ms))
+    }
+}
+
+fn is_retryable(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+}
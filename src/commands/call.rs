@@ -0,0 +1,68 @@
+use anyhow::{Context, Result};
+use serde_json::Value;
+use std::sync::Arc;
+#[cfg(feature = "nats")]
+use tracing::warn;
+
+use crate::audit::AuditLog;
+use crate::cache::Cache;
+use crate::client::TavilyClient;
+#[cfg(feature = "nats")]
+use crate::config::CacheBackendKind;
+use crate::config::Config;
+use crate::tools::registry::{build_tools, content_to_text, find_tool};
+use crate::usage::UsageAccountant;
+
+// Invokes a single tool with JSON arguments and prints its output
+pub async fn run(config: &Config, tool_name: &str, args: &str) -> Result<()> {
+    let arguments: Value = serde_json::from_str(args).context("--args must be a JSON object")?;
+    if !arguments.is_object() {
+        return Err(anyhow::anyhow!("--args must be a JSON object"));
+    }
+
+    let client = Arc::new(TavilyClient::new(
+        config.api_keys()?,
+        &config.tavily,
+        &config.retry,
+        &config.rate_limit,
+        &config.circuit_breaker,
+    )?);
+    let cache = open_cache(config).await;
+    let audit = AuditLog::from_config(&config.audit, &config.transport.nats_url).await?;
+    let usage = Arc::new(UsageAccountant::new(config.budget.clone()));
+    let tools = build_tools(&config.tools, client, cache, audit, usage, None);
+
+    let tool = find_tool(&tools, tool_name).ok_or_else(|| {
+        let available: Vec<String> = tools.iter().map(|tool| tool.name()).collect();
+        anyhow::anyhow!(
+            "Unknown tool '{}'. Available tools: {}",
+            tool_name,
+            available.join(", ")
+        )
+    })?;
+
+    let content = tool.call(Some(arguments)).await?;
+    println!("{}", content_to_text(&content));
+    Ok(())
+}
+
+// `call` has no server connection, so it opens one only when the cache needs it
+#[cfg(feature = "nats")]
+async fn open_cache(config: &Config) -> Cache {
+    let nats = match config.cache.backend {
+        CacheBackendKind::Nats => match async_nats::connect(&config.transport.nats_url).await {
+            Ok(client) => Some(client),
+            Err(e) => {
+                warn!("Failed to connect to NATS for the cache: {}", e);
+                None
+            }
+        },
+        _ => None,
+    };
+    Cache::from_config(&config.cache, nats.as_ref()).await
+}
+
+#[cfg(not(feature = "nats"))]
+async fn open_cache(config: &Config) -> Cache {
+    Cache::from_config(&config.cache).await
+}
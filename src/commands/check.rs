@@ -0,0 +1,81 @@
+use anyhow::Result;
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::auth::Authenticator;
+use crate::auth::policy::Policy;
+use crate::client::TavilyClient;
+use crate::config::{AuthMode, Config};
+use crate::usage::AccountUsage;
+
+// Validates the configuration and, unless offline, that Tavily accepts every API key
+pub async fn run(config: &Config, offline: bool) -> Result<()> {
+    let keys = config.api_keys()?;
+    check_auth(config)?;
+    println!("Configuration OK");
+
+    if offline {
+        return Ok(());
+    }
+
+    let client = Arc::new(TavilyClient::new(
+        keys,
+        &config.tavily,
+        &config.retry,
+        &config.rate_limit,
+        &config.circuit_breaker,
+    )?);
+
+    // The usage endpoint costs no credits and can be asked with each key in turn
+    let reports = AccountUsage::new(client, &config.tools.usage)
+        .fetch(true)
+        .await;
+    let mut rejected = 0;
+    for report in &reports {
+        match (&report.usage, &report.error) {
+            (_, Some(error)) => {
+                rejected += 1;
+                println!("API key {} failed: {}", report.label, error);
+            }
+            (Some(usage), None) => match usage.remaining_credits() {
+                Some(remaining) => println!(
+                    "API key {} accepted ({} credit(s) remaining)",
+                    report.label, remaining
+                ),
+                None => println!("API key {} accepted", report.label),
+            },
+            (None, None) => println!("API key {} accepted", report.label),
+        }
+    }
+
+    if rejected > 0 {
+        return Err(anyhow::anyhow!(
+            "{} of {} API key(s) failed the check against {}",
+            rejected,
+            reports.len(),
+            config.tavily.base_url
+        ));
+    }
+    Ok(())
+}
+
+// Loads the policy and builds the authenticator the way `serve` does, so a
+// missing policy file or an unusable JWT key fails here rather than at startup
+fn check_auth(config: &Config) -> Result<()> {
+    if config.auth.mode == AuthMode::None {
+        return Ok(());
+    }
+
+    let policy = match &config.auth.policy_file {
+        Some(path) => Policy::from_file(Path::new(path))?,
+        None => Policy::default(),
+    };
+    let clients = policy.clients.len();
+    Authenticator::new(&config.auth, Arc::new(policy))?;
+
+    println!(
+        "Authentication OK ({:?}, {} client(s))",
+        config.auth.mode, clients
+    );
+    Ok(())
+}
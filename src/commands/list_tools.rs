@@ -0,0 +1,43 @@
+use anyhow::Result;
+use serde_json::json;
+use std::sync::Arc;
+
+use crate::audit::AuditLog;
+use crate::cache::Cache;
+use crate::client::TavilyClient;
+use crate::config::Config;
+use crate::tools::registry::build_tools;
+use crate::usage::UsageAccountant;
+
+// Prints the name, description and input schema of every enabled tool
+pub fn run(config: &Config) -> Result<()> {
+    // Listing schemas never reaches Tavily, so a missing key is fine here
+    let client = Arc::new(TavilyClient::new(
+        config.api_keys().unwrap_or_default(),
+        &config.tavily,
+        &config.retry,
+        &config.rate_limit,
+        &config.circuit_breaker,
+    )?);
+
+    let tools: Vec<_> = build_tools(
+        &config.tools,
+        client,
+        Cache::disabled(),
+        AuditLog::disabled(),
+        Arc::new(UsageAccountant::new(config.budget.clone())),
+        None,
+    )
+    .iter()
+    .map(|tool| {
+        json!({
+            "name": tool.name(),
+            "description": tool.description(),
+            "inputSchema": tool.input_schema(),
+        })
+    })
+    .collect();
+
+    println!("{}", serde_json::to_string_pretty(&tools)?);
+    Ok(())
+}
@@ -0,0 +1,6 @@
+pub mod call;
+pub mod check;
+pub mod list_tools;
+#[cfg(feature = "nats")]
+pub mod serve;
+pub mod usage;
@@ -0,0 +1,181 @@
+use anyhow::Result;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+use tracing::{error, info, warn};
+
+#[cfg(feature = "http")]
+use crate::admin;
+use crate::audit::AuditLog;
+use crate::auth::Authenticator;
+use crate::auth::policy::Policy;
+use crate::cache::Cache;
+use crate::client::TavilyClient;
+use crate::config::{AuthMode, Config};
+use crate::health::Health;
+use crate::jobs::{JobQueue, JobQueueConfig};
+use crate::server::McpServer;
+use crate::shutdown::{self, Shutdown};
+use crate::tools::job::{AsyncJobTool, TavilyJobStatusTool};
+use crate::tools::registry::{SharedTool, build_tools};
+use crate::transport::nats::NatsTransport;
+use crate::usage::UsageAccountant;
+
+pub async fn run(config: Config) -> Result<()> {
+    info!("Starting Tavily MCP server");
+
+    let keys = match config.api_keys() {
+        Ok(keys) => {
+            info!(
+                "Found {} Tavily API key(s): {}",
+                keys.len(),
+                keys.iter()
+                    .map(|key| key.label.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
+            keys
+        }
+        Err(e) => {
+            error!("{}", e);
+            error!("Please set your API key in the .env file, environment or config file");
+            return Err(e);
+        }
+    };
+
+    // Create NATS transport
+    let nats_url = &config.transport.nats_url;
+    let subject = &config.transport.subject;
+
+    info!("Connecting to NATS at {} on subject {}", nats_url, subject);
+    let transport = NatsTransport::connect(nats_url, subject).await?;
+    info!("Successfully connected to NATS");
+
+    // Health checks and the job queue share the transport's connection, so
+    // readiness reflects the connection that requests actually arrive on
+    let nats_client = transport.client().clone();
+
+    // Set up the response cache
+    let cache = Cache::from_config(&config.cache, Some(&nats_client)).await;
+    let audit = AuditLog::from_config(&config.audit, nats_url).await?;
+
+    // Initialize tools
+    info!("Initializing Tavily tools...");
+    let client = Arc::new(TavilyClient::new(
+        keys,
+        &config.tavily,
+        &config.retry,
+        &config.rate_limit,
+        &config.circuit_breaker,
+    )?);
+
+    // Authenticate callers and apply their per-client policy
+    let policy = match config.auth.mode {
+        AuthMode::None => None,
+        AuthMode::Token | AuthMode::Jwt => Some(Arc::new(match &config.auth.policy_file {
+            Some(path) => Policy::from_file(Path::new(path))?,
+            None => Policy::default(),
+        })),
+    };
+    let auth = match &policy {
+        Some(policy) => Some(Arc::new(Authenticator::new(&config.auth, policy.clone())?)),
+        None => None,
+    };
+    // Credits are counted in the shared bucket so budgets hold across replicas and restarts
+    let usage = UsageAccountant::from_config(config.budget.clone(), policy, &nats_client).await?;
+    let tools = build_tools(
+        &config.tools,
+        client.clone(),
+        cache.clone(),
+        audit.clone(),
+        Arc::new(usage),
+        auth.clone(),
+    );
+    let shutdown = Shutdown::new();
+
+    // Readiness follows the NATS connection state and is lost while draining
+    let health = Health::new(nats_client.clone(), client, cache.clone(), shutdown.clone());
+    let health_subject = config.health.subject.clone();
+    let health_responder = health.clone();
+    tokio::spawn(async move {
+        if let Err(e) = health_responder.serve_nats(health_subject).await {
+            error!("Health responder stopped: {}", e);
+        }
+    });
+    #[cfg(not(feature = "http"))]
+    if config.admin.enabled {
+        warn!("Admin server is enabled but this build excludes it (enable the `http` feature)");
+    }
+    #[cfg(feature = "http")]
+    if config.admin.enabled {
+        let listen = config.admin.listen.clone();
+        let health = health.clone();
+        tokio::spawn(async move {
+            if let Err(e) = admin::serve(&listen, health).await {
+                error!("{:#}", e);
+            }
+        });
+    }
+
+    // Optionally run long jobs through the JetStream-backed queue
+    let served: Vec<SharedTool> = if config.jobs.enabled {
+        info!("Initializing JetStream job queue...");
+        let queue = JobQueue::new(nats_client, JobQueueConfig::default()).await?;
+
+        let worker_tools: HashMap<String, SharedTool> = tools
+            .iter()
+            .map(|tool| (tool.name(), tool.clone()))
+            .collect();
+        let worker_queue = queue.clone();
+        let worker_shutdown = shutdown.clone();
+        tokio::spawn(async move {
+            if let Err(e) = worker_queue.run_worker(worker_tools, worker_shutdown).await {
+                error!("Job worker stopped: {}", e);
+            }
+        });
+
+        let mut served: Vec<SharedTool> = tools
+            .into_iter()
+            .map(|tool| Arc::new(AsyncJobTool::new(tool, queue.clone())) as SharedTool)
+            .collect();
+        served.push(Arc::new(TavilyJobStatusTool::new(queue)));
+        served
+    } else {
+        tools
+    };
+
+    let server = McpServer::new(served, auth, &shutdown);
+
+    info!("Listening for requests on NATS subject: {}", subject);
+    let running = transport.serve(server);
+    tokio::pin!(running);
+
+    let signal = tokio::select! {
+        result = &mut running => return result,
+        signal = shutdown::signal() => signal?,
+    };
+
+    // Stop taking requests first, so that everything received can be waited for
+    info!("Received {}, draining the NATS subscription", signal);
+    transport.stop();
+    running.await?;
+
+    info!("Waiting for {} in-flight call(s)", shutdown.in_flight());
+    shutdown.start_draining();
+    let grace = config.shutdown.grace_period();
+    if !shutdown.wait_idle(grace).await {
+        warn!(
+            "Grace period of {:?} expired with {} call(s) still in flight",
+            grace,
+            shutdown.in_flight()
+        );
+    }
+    if let Err(e) = transport.client().flush().await {
+        warn!("Failed to flush NATS replies: {}", e);
+    }
+
+    audit.flush().await;
+    cache.flush().await;
+    info!("Shutdown complete");
+    Ok(())
+}
@@ -0,0 +1,39 @@
+use anyhow::Result;
+use std::sync::Arc;
+
+use crate::client::TavilyClient;
+use crate::config::Config;
+use crate::usage::AccountUsage;
+
+// Prints the key and plan usage reported by Tavily for each configured key
+pub async fn run(config: &Config) -> Result<()> {
+    let client = Arc::new(TavilyClient::new(
+        config.api_keys()?,
+        &config.tavily,
+        &config.retry,
+        &config.rate_limit,
+        &config.circuit_breaker,
+    )?);
+
+    let reports = AccountUsage::new(client, &config.tools.usage)
+        .fetch(true)
+        .await;
+    println!("{}", serde_json::to_string_pretty(&reports)?);
+
+    for report in &reports {
+        if let Some(remaining) = report.usage.as_ref().and_then(|u| u.remaining_credits()) {
+            println!("Remaining credits for {}: {}", report.label, remaining);
+        }
+    }
+
+    // Only fail when no key could be checked at all
+    if let Some(error) = reports
+        .iter()
+        .map(|report| report.error.as_deref())
+        .collect::<Option<Vec<_>>>()
+        .and_then(|errors| errors.first().copied())
+    {
+        return Err(anyhow::anyhow!("{}", error));
+    }
+    Ok(())
+}
@@ -0,0 +1,792 @@
+use crate::cli::Cli;
+use crate::keys::ApiKey;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+use std::time::Duration;
+
+const API_KEY_PLACEHOLDER: &str = "your_api_key_here";
+
+// Effective server configuration, layered as CLI > env > file > defaults
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub tavily: TavilyConfig,
+    pub transport: TransportConfig,
+    pub tools: ToolsConfig,
+    pub cache: CacheConfig,
+    pub jobs: JobsConfig,
+    pub retry: RetryConfig,
+    pub rate_limit: RateLimitConfig,
+    pub circuit_breaker: CircuitBreakerConfig,
+    pub logging: LoggingConfig,
+    pub tracing: TracingConfig,
+    pub admin: AdminConfig,
+    pub health: HealthConfig,
+    pub audit: AuditConfig,
+    pub budget: BudgetConfig,
+    pub auth: AuthConfig,
+    pub shutdown: ShutdownConfig,
+    /// Problems found while loading, kept until logging is set up.
+    #[serde(skip)]
+    warnings: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TavilyConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub api_key: Option<String>,
+    /// Additional keys, each either a bare key or `label:key`.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub api_keys: Vec<String>,
+    pub key_strategy: KeyStrategy,
+    /// How long a key is skipped after a 401, 429 or quota error.
+    pub key_cooldown_secs: u64,
+    /// Whether callers may pass their own key in `_meta.tavily_api_key`.
+    pub client_key_policy: ClientKeyPolicy,
+    pub base_url: String,
+    pub timeout_secs: u64,
+}
+
+impl Default for TavilyConfig {
+    fn default() -> Self {
+        Self {
+            api_key: None,
+            api_keys: Vec::new(),
+            key_strategy: KeyStrategy::RoundRobin,
+            key_cooldown_secs: 60,
+            client_key_policy: ClientKeyPolicy::Forbid,
+            base_url: "https://api.tavily.com".to_string(),
+            timeout_secs: 30,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum KeyStrategy {
+    RoundRobin,
+    LeastUsed,
+    Failover,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ClientKeyPolicy {
+    /// Reject calls that carry their own key.
+    Forbid,
+    /// Use the caller's key when given, the server's keys otherwise.
+    Allow,
+    /// Reject calls without their own key.
+    Require,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TransportConfig {
+    pub nats_url: String,
+    pub subject: String,
+}
+
+impl Default for TransportConfig {
+    fn default() -> Self {
+        Self {
+            nats_url: "nats://localhost:4222".to_string(),
+            subject: "mcp.requests".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ToolsConfig {
+    pub search: ToolConfig,
+    pub extract: ToolConfig,
+    pub usage: UsageToolConfig,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ToolConfig {
+    pub enabled: bool,
+    /// Parameters applied when the caller does not set them.
+    pub defaults: Map<String, Value>,
+    /// Parameters that always replace the caller's values.
+    pub forced: Map<String, Value>,
+}
+
+impl Default for ToolConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            defaults: Map::new(),
+            forced: Map::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct UsageToolConfig {
+    /// How long an account usage answer from Tavily is reused; 0 disables caching.
+    pub cache_secs: u64,
+    /// Log a warning when fewer credits than this remain on the key or plan.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub warn_below_credits: Option<u64>,
+}
+
+impl Default for UsageToolConfig {
+    fn default() -> Self {
+        Self {
+            cache_secs: 60,
+            warn_below_credits: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CacheBackendKind {
+    None,
+    Memory,
+    Nats,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CacheConfig {
+    pub backend: CacheBackendKind,
+    pub ttl_secs: u64,
+    /// How long expired entries are kept to answer when Tavily is failing.
+    pub stale_grace_secs: u64,
+    /// How often a stale entry that was served is retried against Tavily.
+    pub revalidate_interval_secs: u64,
+    pub bucket: String,
+    /// Most entries the `memory` backend holds; the oldest is evicted beyond that.
+    pub max_entries: usize,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            backend: CacheBackendKind::None,
+            ttl_secs: 3600,
+            stale_grace_secs: 86_400,
+            revalidate_interval_secs: 30,
+            bucket: "tavily_cache".to_string(),
+            max_entries: 1000,
+        }
+    }
+}
+
+impl CacheConfig {
+    pub fn ttl(&self) -> Duration {
+        Duration::from_secs(self.ttl_secs)
+    }
+
+    pub fn stale_grace(&self) -> Duration {
+        Duration::from_secs(self.stale_grace_secs)
+    }
+
+    pub fn revalidate_interval(&self) -> Duration {
+        Duration::from_secs(self.revalidate_interval_secs)
+    }
+
+    /// How long backends keep an entry: the TTL plus the stale grace period.
+    pub fn retention(&self) -> Duration {
+        self.ttl() + self.stale_grace()
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct JobsConfig {
+    pub enabled: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RetryConfig {
+    /// Retries after the first attempt for 429, 5xx and connection errors.
+    pub max_retries: u32,
+    pub initial_backoff_ms: u64,
+    pub max_backoff_ms: u64,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            max_retries: 2,
+            initial_backoff_ms: 500,
+            max_backoff_ms: 5000,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RateLimitConfig {
+    /// Upstream requests per second across all tools; unlimited when unset.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub requests_per_second: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CircuitBreakerConfig {
+    pub enabled: bool,
+    /// Share of failed requests (5xx and connection errors) that opens the breaker.
+    pub failure_rate: f64,
+    /// Requests in the window before the failure rate is considered.
+    pub min_requests: u32,
+    pub window_secs: u64,
+    /// How long an open breaker fails fast before probing the endpoint again.
+    pub open_secs: u64,
+    /// Requests let through while half-open.
+    pub half_open_probes: u32,
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            failure_rate: 0.5,
+            min_requests: 10,
+            window_secs: 60,
+            open_secs: 30,
+            half_open_probes: 1,
+        }
+    }
+}
+
+impl CircuitBreakerConfig {
+    pub fn window(&self) -> Duration {
+        Duration::from_secs(self.window_secs)
+    }
+
+    pub fn open_duration(&self) -> Duration {
+        Duration::from_secs(self.open_secs)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    Text,
+    Json,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LoggingConfig {
+    /// An `EnvFilter` directive such as `info` or `tavily_anet_mcp=debug`.
+    pub level: String,
+    pub format: LogFormat,
+    /// Field names whose values are replaced with `[REDACTED]` in log output.
+    pub redact_fields: Vec<String>,
+    /// Logged request and response payloads are cut off after this many bytes.
+    pub max_payload_bytes: usize,
+}
+
+impl Default for LoggingConfig {
+    fn default() -> Self {
+        Self {
+            level: "info".to_string(),
+            format: LogFormat::Text,
+            redact_fields: vec![
+                "api_key".to_string(),
+                "tavily_api_key".to_string(),
+                "authorization".to_string(),
+                "raw_content".to_string(),
+            ],
+            max_payload_bytes: 2048,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TracingConfig {
+    /// OTLP gRPC endpoint such as `http://localhost:4317`; spans are only exported when set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub otlp_endpoint: Option<String>,
+    pub service_name: String,
+}
+
+impl Default for TracingConfig {
+    fn default() -> Self {
+        Self {
+            otlp_endpoint: None,
+            service_name: "tavily-mcp".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AdminConfig {
+    /// Serve `/metrics`, `/healthz` and `/readyz` over HTTP.
+    pub enabled: bool,
+    pub listen: String,
+}
+
+impl Default for AdminConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            listen: "0.0.0.0:9464".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct HealthConfig {
+    /// NATS subject answering health checks with the JSON report.
+    pub subject: String,
+}
+
+impl Default for HealthConfig {
+    fn default() -> Self {
+        Self {
+            subject: "tavily.health".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AuditSinkKind {
+    None,
+    File,
+    Nats,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AuditConfig {
+    pub sink: AuditSinkKind,
+    /// JSONL file written by the `file` sink.
+    pub path: String,
+    /// The file is rotated once it reaches this size.
+    pub max_bytes: u64,
+    /// Rotated files kept next to the active one.
+    pub max_files: u32,
+    /// Subject the `nats` sink publishes to.
+    pub subject: String,
+    /// Field names whose values are replaced with `[REDACTED]` in audit records.
+    pub redact_fields: Vec<String>,
+}
+
+impl Default for AuditConfig {
+    fn default() -> Self {
+        Self {
+            sink: AuditSinkKind::None,
+            path: "tavily-audit.jsonl".to_string(),
+            max_bytes: 100 * 1024 * 1024,
+            max_files: 5,
+            subject: "tavily.audit".to_string(),
+            redact_fields: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UsageStoreKind {
+    Memory,
+    Nats,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BudgetConfig {
+    /// Estimated credits allowed per UTC day; unlimited when unset.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub daily_credits: Option<u64>,
+    /// Estimated credits allowed per UTC calendar month; unlimited when unset.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub monthly_credits: Option<u64>,
+    /// Where `serve` counts credits: a KV bucket shared by all replicas, or memory.
+    pub store: UsageStoreKind,
+    pub bucket: String,
+}
+
+impl Default for BudgetConfig {
+    fn default() -> Self {
+        Self {
+            daily_credits: None,
+            monthly_credits: None,
+            store: UsageStoreKind::Nats,
+            bucket: "tavily_usage".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AuthMode {
+    None,
+    Token,
+    Jwt,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AuthConfig {
+    pub mode: AuthMode,
+    /// TOML file mapping client IDs to their token, tools, parameters, rate limit and budget.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub policy_file: Option<String>,
+    /// HS256 secret for verifying JWTs.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub jwt_secret: Option<String>,
+    /// RS256 public key (PEM) for verifying JWTs; takes precedence over `jwt_secret`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub jwt_public_key_file: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub jwt_issuer: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub jwt_audience: Option<String>,
+}
+
+impl Default for AuthConfig {
+    fn default() -> Self {
+        Self {
+            mode: AuthMode::None,
+            policy_file: None,
+            jwt_secret: None,
+            jwt_public_key_file: None,
+            jwt_issuer: None,
+            jwt_audience: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ShutdownConfig {
+    /// How long in-flight tool calls may take to finish after SIGINT or SIGTERM.
+    pub grace_period_secs: u64,
+}
+
+impl Default for ShutdownConfig {
+    fn default() -> Self {
+        Self {
+            grace_period_secs: 30,
+        }
+    }
+}
+
+impl ShutdownConfig {
+    pub fn grace_period(&self) -> Duration {
+        Duration::from_secs(self.grace_period_secs)
+    }
+}
+
+impl Config {
+    pub fn load(cli: &Cli) -> Result<Self> {
+        let path = cli
+            .config
+            .clone()
+            .or_else(|| env::var("MCP_CONFIG").ok().map(Into::into));
+
+        let mut config = match path {
+            Some(path) => Self::from_file(&path)?,
+            None => Self::default(),
+        };
+
+        config.apply_env();
+        config.apply_cli(cli);
+        Ok(config)
+    }
+
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config file {}", path.display()))?;
+        toml::from_str(&contents)
+            .with_context(|| format!("Failed to parse config file {}", path.display()))
+    }
+
+    fn apply_env(&mut self) {
+        if let Ok(key) = env::var("TAVILY_API_KEY") {
+            self.tavily.api_key = Some(key);
+        }
+        if let Ok(keys) = env::var("TAVILY_API_KEYS") {
+            self.tavily.api_keys = keys
+                .split(',')
+                .map(|key| key.trim().to_string())
+                .filter(|key| !key.is_empty())
+                .collect();
+        }
+        if let Ok(strategy) = env::var("TAVILY_KEY_STRATEGY") {
+            match strategy.as_str() {
+                "round-robin" => self.tavily.key_strategy = KeyStrategy::RoundRobin,
+                "least-used" => self.tavily.key_strategy = KeyStrategy::LeastUsed,
+                "failover" => self.tavily.key_strategy = KeyStrategy::Failover,
+                other => self
+                    .warnings
+                    .push(format!("Ignoring unknown TAVILY_KEY_STRATEGY '{}'", other)),
+            }
+        }
+        if let Ok(policy) = env::var("TAVILY_CLIENT_KEY_POLICY") {
+            match policy.as_str() {
+                "forbid" => self.tavily.client_key_policy = ClientKeyPolicy::Forbid,
+                "allow" => self.tavily.client_key_policy = ClientKeyPolicy::Allow,
+                "require" => self.tavily.client_key_policy = ClientKeyPolicy::Require,
+                other => self.warnings.push(format!(
+                    "Ignoring unknown TAVILY_CLIENT_KEY_POLICY '{}'",
+                    other
+                )),
+            }
+        }
+        if let Ok(url) = env::var("TAVILY_BASE_URL") {
+            self.tavily.base_url = url;
+        }
+        if let Ok(url) = env::var("NATS_URL") {
+            self.transport.nats_url = url;
+        }
+        if let Ok(subject) = env::var("MCP_SUBJECT") {
+            self.transport.subject = subject;
+        }
+        if let Ok(enabled) = env::var("MCP_JOBS_ENABLED") {
+            match parse_bool(&enabled) {
+                Some(enabled) => self.jobs.enabled = enabled,
+                None => self
+                    .warnings
+                    .push(format!("Ignoring invalid MCP_JOBS_ENABLED '{}'", enabled)),
+            }
+        }
+        if let Ok(backend) = env::var("MCP_CACHE_BACKEND") {
+            match backend.as_str() {
+                "none" => self.cache.backend = CacheBackendKind::None,
+                "memory" => self.cache.backend = CacheBackendKind::Memory,
+                "nats" => self.cache.backend = CacheBackendKind::Nats,
+                other => self
+                    .warnings
+                    .push(format!("Ignoring unknown MCP_CACHE_BACKEND '{}'", other)),
+            }
+        }
+        if let Some(ttl) = self.env_number("MCP_CACHE_TTL_SECS") {
+            self.cache.ttl_secs = ttl;
+        }
+        if let Some(grace) = self.env_number("MCP_CACHE_STALE_GRACE_SECS") {
+            self.cache.stale_grace_secs = grace;
+        }
+        if let Ok(bucket) = env::var("MCP_CACHE_BUCKET") {
+            self.cache.bucket = bucket;
+        }
+        if let Ok(listen) = env::var("MCP_ADMIN_LISTEN") {
+            self.admin.enabled = true;
+            self.admin.listen = listen;
+        }
+        if let Ok(subject) = env::var("MCP_HEALTH_SUBJECT") {
+            self.health.subject = subject;
+        }
+        if let Ok(endpoint) = env::var("OTEL_EXPORTER_OTLP_ENDPOINT") {
+            self.tracing.otlp_endpoint = Some(endpoint);
+        }
+        if let Ok(name) = env::var("OTEL_SERVICE_NAME") {
+            self.tracing.service_name = name;
+        }
+        if let Ok(sink) = env::var("MCP_AUDIT_SINK") {
+            match sink.as_str() {
+                "none" => self.audit.sink = AuditSinkKind::None,
+                "file" => self.audit.sink = AuditSinkKind::File,
+                "nats" => self.audit.sink = AuditSinkKind::Nats,
+                other => self
+                    .warnings
+                    .push(format!("Ignoring unknown MCP_AUDIT_SINK '{}'", other)),
+            }
+        }
+        if let Ok(path) = env::var("MCP_AUDIT_PATH") {
+            self.audit.path = path;
+        }
+        if let Some(credits) = self.env_number("MCP_DAILY_CREDIT_BUDGET") {
+            self.budget.daily_credits = Some(credits);
+        }
+        if let Some(credits) = self.env_number("MCP_MONTHLY_CREDIT_BUDGET") {
+            self.budget.monthly_credits = Some(credits);
+        }
+        if let Ok(store) = env::var("MCP_BUDGET_STORE") {
+            match store.as_str() {
+                "memory" => self.budget.store = UsageStoreKind::Memory,
+                "nats" => self.budget.store = UsageStoreKind::Nats,
+                other => self
+                    .warnings
+                    .push(format!("Ignoring unknown MCP_BUDGET_STORE '{}'", other)),
+            }
+        }
+        if let Ok(mode) = env::var("MCP_AUTH_MODE") {
+            match mode.as_str() {
+                "none" => self.auth.mode = AuthMode::None,
+                "token" => self.auth.mode = AuthMode::Token,
+                "jwt" => self.auth.mode = AuthMode::Jwt,
+                other => self
+                    .warnings
+                    .push(format!("Ignoring unknown MCP_AUTH_MODE '{}'", other)),
+            }
+        }
+        if let Ok(path) = env::var("MCP_POLICY_FILE") {
+            self.auth.policy_file = Some(path);
+        }
+        if let Ok(secret) = env::var("MCP_JWT_SECRET") {
+            self.auth.jwt_secret = Some(secret);
+        }
+        if let Some(grace) = self.env_number("MCP_SHUTDOWN_GRACE_SECS") {
+            self.shutdown.grace_period_secs = grace;
+        }
+        if let Ok(level) = env::var("RUST_LOG") {
+            self.logging.level = level;
+        }
+        if let Ok(format) = env::var("MCP_LOG_FORMAT") {
+            match format.as_str() {
+                "text" => self.logging.format = LogFormat::Text,
+                "json" => self.logging.format = LogFormat::Json,
+                other => self
+                    .warnings
+                    .push(format!("Ignoring unknown MCP_LOG_FORMAT '{}'", other)),
+            }
+        }
+    }
+
+    // Reads a numeric variable, warning about values that don't parse
+    fn env_number<T: FromStr>(&mut self, name: &str) -> Option<T> {
+        let value = env::var(name).ok()?;
+        match value.trim().parse() {
+            Ok(number) => Some(number),
+            Err(_) => {
+                self.warnings
+                    .push(format!("Ignoring invalid {} '{}'", name, value));
+                None
+            }
+        }
+    }
+
+    /// Takes the warnings collected while loading, which are only logged once
+    /// the log subscriber is installed.
+    pub fn take_warnings(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.warnings)
+    }
+
+    fn apply_cli(&mut self, cli: &Cli) {
+        if let Some(url) = &cli.nats_url {
+            self.transport.nats_url = url.clone();
+        }
+        if let Some(subject) = &cli.subject {
+            self.transport.subject = subject.clone();
+        }
+        if let Some(level) = &cli.log_level {
+            self.logging.level = level.clone();
+        }
+    }
+
+    /// Returns every configured API key, rejecting missing, empty and placeholder values.
+    /// `api_key` comes first, followed by `api_keys`.
+    pub fn api_keys(&self) -> Result<Vec<ApiKey>> {
+        let keys = self.parsed_keys();
+        if keys.is_empty() {
+            return Err(anyhow::anyhow!(
+                "TAVILY_API_KEY or TAVILY_API_KEYS environment variable is required"
+            ));
+        }
+
+        for key in &keys {
+            match key.secret.as_str() {
+                "" => return Err(anyhow::anyhow!("Tavily API key {} is empty", key.label)),
+                API_KEY_PLACEHOLDER => {
+                    return Err(anyhow::anyhow!(
+                        "Tavily API key {} is set to the example placeholder. Please use your actual API key.",
+                        key.label
+                    ));
+                }
+                _ => {}
+            }
+        }
+        Ok(keys)
+    }
+
+    /// Raw values of every configured key and secret, for scrubbing from logs.
+    pub fn secrets(&self) -> Vec<String> {
+        self.parsed_keys()
+            .into_iter()
+            .map(|key| key.secret)
+            .chain(self.auth.jwt_secret.clone())
+            .filter(|secret| !secret.is_empty())
+            .collect()
+    }
+
+    fn parsed_keys(&self) -> Vec<ApiKey> {
+        self.tavily
+            .api_key
+            .iter()
+            .chain(&self.tavily.api_keys)
+            .enumerate()
+            .map(|(index, entry)| ApiKey::parse(entry, index))
+            .collect()
+    }
+
+    /// Copy of the configuration that is safe to print or log.
+    pub fn redacted(&self) -> Self {
+        let mut config = self.clone();
+        config.tavily.api_key = config.tavily.api_key.as_deref().map(redact_secret);
+        config.tavily.api_keys = self
+            .parsed_keys()
+            .into_iter()
+            .skip(usize::from(self.tavily.api_key.is_some()))
+            .map(|key| format!("{}:{}", key.label, redact_secret(&key.secret)))
+            .collect();
+        config.auth.jwt_secret = config.auth.jwt_secret.as_deref().map(redact_secret);
+        config
+    }
+
+    pub fn to_toml(&self) -> Result<String> {
+        toml::to_string_pretty(self).context("Failed to serialize configuration")
+    }
+}
+
+/// Masks a secret for display. Tavily keys keep their public `tvly-` prefix
+/// so the kind of key is still visible; other secrets show nothing.
+pub fn redact_secret(secret: &str) -> String {
+    if secret.starts_with("tvly-") && secret.chars().count() > 8 {
+        return "tvly-...".to_string();
+    }
+    "***".to_string()
+}
+
+// Accepts the usual spellings; anything else is reported rather than read as false
+fn parse_bool(value: &str) -> Option<bool> {
+    match value.trim().to_ascii_lowercase().as_str() {
+        "1" | "true" | "yes" | "on" => Some(true),
+        "0" | "false" | "no" | "off" | "" => Some(false),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn booleans_accept_common_spellings_only() {
+        for value in ["1", "true", "TRUE", "yes", "on"] {
+            assert_eq!(parse_bool(value), Some(true), "{}", value);
+        }
+        for value in ["0", "false", "no", "off", ""] {
+            assert_eq!(parse_bool(value), Some(false), "{}", value);
+        }
+        assert_eq!(parse_bool("enabled"), None);
+    }
+
+    #[test]
+    fn secrets_only_reveal_the_tavily_prefix() {
+        assert_eq!(redact_secret("tvly-0123456789abcdef"), "tvly-...");
+        assert_eq!(redact_secret("tvly-abc"), "***");
+        assert_eq!(redact_secret("my-jwt-signing-secret"), "***");
+    }
+}
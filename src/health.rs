@@ -0,0 +1,126 @@
+use anyhow::Result;
+use async_nats::connection::State;
+use futures_util::StreamExt;
+use serde::Serialize;
+use std::sync::Arc;
+use tracing::{debug, info, warn};
+
+use crate::cache::Cache;
+use crate::client::TavilyClient;
+use crate::keys::KeyStatus;
+use crate::shutdown::Shutdown;
+use crate::utils::circuit_breaker::BreakerStatus;
+
+// Liveness and readiness of the server and its dependencies
+#[derive(Clone)]
+pub struct Health {
+    nats: async_nats::Client,
+    client: Arc<TavilyClient>,
+    cache: Cache,
+    shutdown: Shutdown,
+}
+
+#[derive(Debug, Serialize)]
+pub struct HealthReport {
+    /// False while NATS is disconnected or the server is draining.
+    pub ready: bool,
+    pub nats: &'static str,
+    pub draining: bool,
+    pub in_flight: usize,
+    pub cache: CacheHealth,
+    pub upstream: UpstreamHealth,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CacheHealth {
+    pub backend: &'static str,
+    pub healthy: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UpstreamHealth {
+    /// Unix time in milliseconds of the last successful Tavily response.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_success: Option<u64>,
+    pub keys: Vec<KeyStatus>,
+    /// Endpoints called so far and the state of their circuit breakers.
+    pub circuit_breakers: Vec<BreakerStatus>,
+}
+
+impl Health {
+    pub fn new(
+        nats: async_nats::Client,
+        client: Arc<TavilyClient>,
+        cache: Cache,
+        shutdown: Shutdown,
+    ) -> Self {
+        Self {
+            nats,
+            client,
+            cache,
+            shutdown,
+        }
+    }
+
+    pub async fn report(&self) -> HealthReport {
+        let nats = match self.nats.connection_state() {
+            State::Connected => "connected",
+            State::Disconnected => "disconnected",
+            State::Pending => "pending",
+        };
+        let draining = self.shutdown.is_draining();
+
+        let cache = match self.cache.check().await {
+            Some((backend, Ok(()))) => CacheHealth {
+                backend,
+                healthy: true,
+                error: None,
+            },
+            Some((backend, Err(e))) => CacheHealth {
+                backend,
+                healthy: false,
+                error: Some(e.to_string()),
+            },
+            None => CacheHealth {
+                backend: "none",
+                healthy: true,
+                error: None,
+            },
+        };
+
+        HealthReport {
+            ready: nats == "connected" && !draining,
+            nats,
+            draining,
+            in_flight: self.shutdown.in_flight(),
+            cache,
+            upstream: UpstreamHealth {
+                last_success: self.client.last_success(),
+                keys: self.client.keys().status(),
+                circuit_breakers: self.client.breakers(),
+            },
+        }
+    }
+
+    /// Answers requests on `subject` with the health report.
+    pub async fn serve_nats(self, subject: String) -> Result<()> {
+        let mut requests = self.nats.subscribe(subject.clone()).await?;
+        info!("Answering health checks on NATS subject {}", subject);
+
+        while let Some(request) = requests.next().await {
+            let Some(reply) = request.reply else {
+                continue;
+            };
+
+            debug!("Health check requested over NATS");
+            let report = serde_json::to_vec(&self.report().await)?;
+            if let Err(e) = self.nats.publish(reply, report.into()).await {
+                warn!("Failed to answer health check: {}", e);
+            }
+        }
+
+        Ok(())
+    }
+}
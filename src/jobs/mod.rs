@@ -0,0 +1,403 @@
+use anyhow::{Context as _, Result};
+use async_nats::jetstream::{self, AckKind, consumer, kv, stream};
+use futures_util::StreamExt;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::time::Duration;
+use tracing::{Instrument, debug, error, info, info_span, warn};
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+use crate::audit::Caller;
+use crate::shutdown::Shutdown;
+use crate::telemetry;
+use crate::tools::registry::{SharedTool, content_to_text};
+use crate::utils::time::unix_millis;
+
+const SUBMIT_SUBJECT: &str = "submit";
+const WORKER_NAME: &str = "tavily-job-worker";
+
+#[derive(Debug, Clone)]
+pub struct JobQueueConfig {
+    pub stream: String,
+    pub subject_prefix: String,
+    pub bucket: String,
+    pub ttl: Duration,
+    pub ack_wait: Duration,
+    /// How often a running job tells JetStream it is still being worked on.
+    pub progress_interval: Duration,
+    pub max_deliver: i64,
+}
+
+impl Default for JobQueueConfig {
+    fn default() -> Self {
+        Self {
+            stream: "TAVILY_JOBS".to_string(),
+            subject_prefix: "tavily.jobs".to_string(),
+            bucket: "tavily_jobs".to_string(),
+            ttl: Duration::from_secs(24 * 60 * 60),
+            ack_wait: Duration::from_secs(60),
+            progress_interval: Duration::from_secs(20),
+            max_deliver: 3,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+    Queued,
+    Running,
+    Completed,
+    Failed,
+}
+
+impl JobStatus {
+    pub fn is_finished(self) -> bool {
+        matches!(self, JobStatus::Completed | JobStatus::Failed)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobRecord {
+    pub id: String,
+    pub tool: String,
+    pub status: JobStatus,
+    pub attempts: u32,
+    /// Unix time in milliseconds.
+    pub created_at: u64,
+    /// Unix time in milliseconds.
+    pub updated_at: u64,
+    /// The client that submitted the job; only it may read the job's status.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owner: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// What the completion subject carries. The result stays in the job record,
+/// where the status tool checks the caller owns the job before returning it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobCompletion {
+    pub id: String,
+    pub status: JobStatus,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct JobSubmission {
+    id: String,
+    tool: String,
+    arguments: Value,
+}
+
+// Durable job queue: submissions go into a work-queue stream, job state lives in a KV bucket
+#[derive(Clone)]
+pub struct JobQueue {
+    client: async_nats::Client,
+    jetstream: jetstream::Context,
+    store: kv::Store,
+    config: JobQueueConfig,
+}
+
+impl JobQueue {
+    pub async fn new(client: async_nats::Client, config: JobQueueConfig) -> Result<Self> {
+        let jetstream = jetstream::new(client.clone());
+
+        jetstream
+            .get_or_create_stream(stream::Config {
+                name: config.stream.clone(),
+                subjects: vec![format!("{}.{}", config.subject_prefix, SUBMIT_SUBJECT)],
+                retention: stream::RetentionPolicy::WorkQueue,
+                max_age: config.ttl,
+                ..Default::default()
+            })
+            .await
+            .with_context(|| format!("Failed to create job stream {}", config.stream))?;
+
+        let store = match jetstream.get_key_value(config.bucket.as_str()).await {
+            Ok(store) => store,
+            Err(_) => jetstream
+                .create_key_value(kv::Config {
+                    bucket: config.bucket.clone(),
+                    description: "Tavily MCP job state".to_string(),
+                    history: 1,
+                    max_age: config.ttl,
+                    ..Default::default()
+                })
+                .await
+                .with_context(|| format!("Failed to create job bucket {}", config.bucket))?,
+        };
+
+        info!(
+            "Job queue ready on stream {} with bucket {}",
+            config.stream, config.bucket
+        );
+
+        Ok(Self {
+            client,
+            jetstream,
+            store,
+            config,
+        })
+    }
+
+    /// Subject on which a `JobCompletion` is published when the job finishes.
+    pub fn completion_subject(&self, id: &str) -> String {
+        format!("{}.done.{}", self.config.subject_prefix, id)
+    }
+
+    /// Queues a call of `tool` on behalf of `owner`, the caller's label.
+    pub async fn submit(&self, tool: &str, arguments: Value, owner: &str) -> Result<JobRecord> {
+        let mut record = new_record(&nuid::next().to_string(), tool);
+        record.owner = Some(owner.to_string());
+
+        // Record the job before publishing so the worker always finds it
+        self.save(&record).await?;
+
+        let submission = JobSubmission {
+            id: record.id.clone(),
+            tool: record.tool.clone(),
+            arguments,
+        };
+        // Carry the caller's trace into the worker
+        let mut headers = async_nats::HeaderMap::new();
+        telemetry::inject_headers(&mut headers);
+
+        self.jetstream
+            .publish_with_headers(
+                format!("{}.{}", self.config.subject_prefix, SUBMIT_SUBJECT),
+                headers,
+                serde_json::to_vec(&submission)?.into(),
+            )
+            .await?
+            .await
+            .context("Job submission was not acknowledged by JetStream")?;
+
+        debug!("Queued job {} for tool {}", record.id, record.tool);
+        Ok(record)
+    }
+
+    pub async fn get(&self, id: &str) -> Result<Option<JobRecord>> {
+        match self.store.get(id).await? {
+            Some(bytes) => Ok(Some(
+                serde_json::from_slice(&bytes).context("Failed to parse job record")?,
+            )),
+            None => Ok(None),
+        }
+    }
+
+    async fn save(&self, record: &JobRecord) -> Result<()> {
+        self.store
+            .put(&record.id, serde_json::to_vec(record)?.into())
+            .await
+            .with_context(|| format!("Failed to store job {}", record.id))?;
+        Ok(())
+    }
+
+    /// Consumes queued jobs until the stream closes or the server starts draining.
+    /// Unacknowledged jobs are redelivered by JetStream, so jobs in flight during a
+    /// restart are picked up again.
+    pub async fn run_worker(
+        self,
+        tools: HashMap<String, SharedTool>,
+        shutdown: Shutdown,
+    ) -> Result<()> {
+        let stream = self.jetstream.get_stream(&self.config.stream).await?;
+        let consumer = stream
+            .get_or_create_consumer(
+                WORKER_NAME,
+                consumer::pull::Config {
+                    durable_name: Some(WORKER_NAME.to_string()),
+                    filter_subject: format!("{}.{}", self.config.subject_prefix, SUBMIT_SUBJECT),
+                    ack_wait: self.config.ack_wait,
+                    max_deliver: self.config.max_deliver,
+                    ..Default::default()
+                },
+            )
+            .await?;
+
+        let mut messages = consumer.messages().await?;
+        info!("Job worker started");
+
+        loop {
+            let message = tokio::select! {
+                message = messages.next() => message,
+                _ = shutdown.draining() => {
+                    info!("Job worker stopped taking new jobs");
+                    break;
+                }
+            };
+            let message = match message {
+                Some(Ok(message)) => message,
+                Some(Err(e)) => {
+                    warn!("Failed to receive job: {}", e);
+                    continue;
+                }
+                None => break,
+            };
+
+            // Leave the job to another replica if shutdown began while it was being fetched
+            let Some(_in_flight) = shutdown.track() else {
+                if let Err(e) = message.ack_with(AckKind::Nak(None)).await {
+                    warn!("Failed to nak job: {}", e);
+                }
+                break;
+            };
+
+            let submission: JobSubmission = match serde_json::from_slice(&message.payload) {
+                Ok(submission) => submission,
+                Err(e) => {
+                    error!("Dropping malformed job submission: {}", e);
+                    if let Err(e) = message.ack_with(AckKind::Term).await {
+                        warn!("Failed to terminate malformed job: {}", e);
+                    }
+                    continue;
+                }
+            };
+
+            let span = info_span!("job.run", job_id = %submission.id, tool = %submission.tool);
+            span.set_parent(telemetry::extract_headers(message.headers.as_ref()));
+
+            let delivered = message.info().map(|info| info.delivered).unwrap_or(1);
+            let (id, tool) = (submission.id.clone(), submission.tool.clone());
+            let work = self.process(&tools, submission).instrument(span);
+            tokio::pin!(work);
+
+            // Long jobs would be redelivered after `ack_wait` and run twice without these
+            let mut progress = tokio::time::interval(self.config.progress_interval);
+            progress.tick().await;
+            let result = loop {
+                tokio::select! {
+                    result = &mut work => break result,
+                    _ = progress.tick() => {
+                        if let Err(e) = message.ack_with(AckKind::Progress).await {
+                            warn!("Failed to report progress on job {}: {}", id, e);
+                        }
+                    }
+                }
+            };
+
+            if let Err(e) = result {
+                error!("Job processing error: {}", e);
+                // JetStream gives up after the last delivery, so the record must not stay running
+                let ack = if delivered >= self.config.max_deliver {
+                    self.give_up(&id, &tool, &e).await;
+                    AckKind::Term
+                } else {
+                    AckKind::Nak(None)
+                };
+                if let Err(e) = message.ack_with(ack).await {
+                    warn!("Failed to nak job {}: {}", id, e);
+                }
+                continue;
+            }
+
+            if let Err(e) = message.ack().await {
+                warn!("Failed to ack job: {}", e);
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn process(
+        &self,
+        tools: &HashMap<String, SharedTool>,
+        submission: JobSubmission,
+    ) -> Result<()> {
+        let mut record = self.get(&submission.id).await?.unwrap_or_else(|| {
+            let mut record = new_record(&submission.id, &submission.tool);
+            record.owner = Some(Caller::from_params(&submission.arguments).label());
+            record
+        });
+
+        // A redelivery after the result was stored but before the ack
+        if record.status.is_finished() {
+            debug!("Job {} already finished, skipping", record.id);
+            return Ok(());
+        }
+
+        record.status = JobStatus::Running;
+        record.attempts += 1;
+        record.updated_at = unix_millis();
+        self.save(&record).await?;
+
+        info!("Running job {} with tool {}", record.id, record.tool);
+
+        let outcome = match tools.get(&submission.tool) {
+            Some(tool) => tool.call(Some(submission.arguments)).await,
+            None => Err(anyhow::anyhow!("Unknown tool: {}", submission.tool)),
+        };
+
+        match outcome {
+            Ok(content) => {
+                record.status = JobStatus::Completed;
+                record.result = Some(content_to_text(&content));
+            }
+            Err(e) => {
+                warn!("Job {} failed: {}", record.id, e);
+                record.status = JobStatus::Failed;
+                record.error = Some(e.to_string());
+            }
+        }
+        self.finish(&mut record).await
+    }
+
+    // Marks a job failed after its last delivery could not be processed
+    async fn give_up(&self, id: &str, tool: &str, error: &anyhow::Error) {
+        let mut record = match self.get(id).await {
+            Ok(Some(record)) => record,
+            Ok(None) => new_record(id, tool),
+            Err(e) => {
+                warn!("Failed to load job {} to mark it failed: {}", id, e);
+                new_record(id, tool)
+            }
+        };
+        record.status = JobStatus::Failed;
+        record.error = Some(format!(
+            "Gave up after {} attempts: {}",
+            record.attempts, error
+        ));
+
+        if let Err(e) = self.finish(&mut record).await {
+            error!("Failed to mark job {} as failed: {}", id, e);
+        }
+    }
+
+    // Stores the final record and announces that it finished on the completion subject
+    async fn finish(&self, record: &mut JobRecord) -> Result<()> {
+        record.updated_at = unix_millis();
+        self.save(record).await?;
+
+        let completion = serde_json::to_vec(&JobCompletion {
+            id: record.id.clone(),
+            status: record.status,
+        })?;
+        if let Err(e) = self
+            .client
+            .publish(self.completion_subject(&record.id), completion.into())
+            .await
+        {
+            warn!("Failed to publish completion for job {}: {}", record.id, e);
+        }
+
+        Ok(())
+    }
+}
+
+fn new_record(id: &str, tool: &str) -> JobRecord {
+    let now = unix_millis();
+    JobRecord {
+        id: id.to_string(),
+        tool: tool.to_string(),
+        status: JobStatus::Queued,
+        attempts: 0,
+        created_at: now,
+        updated_at: now,
+        owner: None,
+        result: None,
+        error: None,
+    }
+}
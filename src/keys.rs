@@ -0,0 +1,342 @@
+use anyhow::Result;
+use reqwest::StatusCode;
+use serde::Serialize;
+use serde_json::Value;
+use std::fmt;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+use crate::config::{ClientKeyPolicy, KeyStrategy};
+use crate::metrics::metrics;
+
+// A Tavily API key with a label that is safe to log and use in metrics
+#[derive(Clone)]
+pub struct ApiKey {
+    pub label: String,
+    pub secret: String,
+}
+
+impl ApiKey {
+    /// Parses `label:key` or a bare key, which is labelled `key-<n>` by position.
+    pub fn parse(entry: &str, index: usize) -> Self {
+        match entry.split_once(':') {
+            Some((label, secret)) if !label.trim().is_empty() => Self {
+                label: label.trim().to_string(),
+                secret: secret.trim().to_string(),
+            },
+            _ => Self {
+                label: format!("key-{}", index + 1),
+                secret: entry.trim().to_string(),
+            },
+        }
+    }
+}
+
+/// Field of the `_meta` argument that carries a caller's own API key.
+pub const CLIENT_KEY_FIELD: &str = "tavily_api_key";
+
+/// Removes the caller's key from `_meta` so it never reaches logs, traces or
+/// Tavily's request body, and checks it against `policy`.
+pub fn take_client_key(params: &mut Value, policy: ClientKeyPolicy) -> Result<Option<ApiKey>> {
+    let secret = params
+        .get_mut("_meta")
+        .and_then(|meta| meta.as_object_mut())
+        .and_then(|meta| meta.remove(CLIENT_KEY_FIELD))
+        .and_then(|key| key.as_str().map(|key| key.trim().to_string()))
+        .filter(|key| !key.is_empty());
+
+    match (policy, secret) {
+        (ClientKeyPolicy::Forbid, Some(_)) => Err(anyhow::anyhow!(
+            "This server does not accept per-request API keys"
+        )),
+        (ClientKeyPolicy::Require, None) => Err(anyhow::anyhow!(
+            "This server requires your own Tavily API key in _meta.{}",
+            CLIENT_KEY_FIELD
+        )),
+        (_, secret) => Ok(secret.map(|secret| ApiKey {
+            label: "client".to_string(),
+            secret,
+        })),
+    }
+}
+
+// Never print the secret, even in debug output
+impl fmt::Debug for ApiKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ApiKey")
+            .field("label", &self.label)
+            .finish_non_exhaustive()
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct KeyStatus {
+    pub label: String,
+    pub requests: u64,
+    pub failures: u64,
+    /// Seconds until the key is used again after a 401, 429 or quota error.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cooldown_remaining_secs: Option<u64>,
+}
+
+// Selects API keys by strategy and benches keys that Tavily rejects for a cooldown period
+pub struct KeyPool {
+    keys: Vec<ApiKey>,
+    strategy: KeyStrategy,
+    cooldown: Duration,
+    state: Mutex<PoolState>,
+}
+
+#[derive(Default)]
+struct PoolState {
+    next: usize,
+    health: Vec<KeyHealth>,
+}
+
+#[derive(Default, Clone)]
+struct KeyHealth {
+    requests: u64,
+    failures: u64,
+    cooldown_until: Option<Instant>,
+}
+
+impl KeyPool {
+    pub fn new(keys: Vec<ApiKey>, strategy: KeyStrategy, cooldown: Duration) -> Self {
+        for key in &keys {
+            metrics().set_key_available(&key.label, true);
+        }
+
+        Self {
+            state: Mutex::new(PoolState {
+                next: 0,
+                health: vec![KeyHealth::default(); keys.len()],
+            }),
+            keys,
+            strategy,
+            cooldown,
+        }
+    }
+
+    pub fn keys(&self) -> &[ApiKey] {
+        &self.keys
+    }
+
+    pub fn labels(&self) -> Vec<&str> {
+        self.keys.iter().map(|key| key.label.as_str()).collect()
+    }
+
+    /// Picks the key for the next request. Keys in cooldown are skipped unless
+    /// every key is cooling down, in which case the one that recovers first is used.
+    pub fn select(&self) -> Option<(usize, &ApiKey)> {
+        let mut state = self.state.lock().unwrap();
+        let now = Instant::now();
+
+        for (index, health) in state.health.iter_mut().enumerate() {
+            if health.cooldown_until.is_some_and(|until| until <= now) {
+                health.cooldown_until = None;
+                info!("Tavily key {} is back in rotation", self.keys[index].label);
+                metrics().set_key_available(&self.keys[index].label, true);
+            }
+        }
+
+        let available: Vec<usize> = (0..self.keys.len())
+            .filter(|index| state.health[*index].cooldown_until.is_none())
+            .collect();
+
+        let index = if available.is_empty() {
+            (0..self.keys.len()).min_by_key(|index| state.health[*index].cooldown_until)?
+        } else {
+            match self.strategy {
+                KeyStrategy::RoundRobin => {
+                    let next = state.next;
+                    let index = available
+                        .iter()
+                        .copied()
+                        .find(|index| *index >= next)
+                        .unwrap_or(available[0]);
+                    state.next = index + 1;
+                    index
+                }
+                KeyStrategy::LeastUsed => available
+                    .iter()
+                    .copied()
+                    .min_by_key(|index| state.health[*index].requests)?,
+                KeyStrategy::Failover => available[0],
+            }
+        };
+
+        state.health[index].requests += 1;
+        Some((index, &self.keys[index]))
+    }
+
+    /// Puts the key in cooldown if `status` means it is invalid, throttled or out of quota.
+    /// Returns true when another key can take over the request.
+    pub fn report_failure(&self, index: usize, status: StatusCode) -> bool {
+        if !is_key_failure(status) {
+            return false;
+        }
+
+        let mut state = self.state.lock().unwrap();
+        let label = &self.keys[index].label;
+        warn!(
+            "Tavily key {} rejected with {}, cooling down for {:?}",
+            label, status, self.cooldown
+        );
+        state.health[index].failures += 1;
+        state.health[index].cooldown_until = Some(Instant::now() + self.cooldown);
+        metrics().set_key_available(label, false);
+
+        state
+            .health
+            .iter()
+            .any(|health| health.cooldown_until.is_none())
+    }
+
+    pub fn status(&self) -> Vec<KeyStatus> {
+        let state = self.state.lock().unwrap();
+        let now = Instant::now();
+
+        self.keys
+            .iter()
+            .zip(&state.health)
+            .map(|(key, health)| KeyStatus {
+                label: key.label.clone(),
+                requests: health.requests,
+                failures: health.failures,
+                cooldown_remaining_secs: health
+                    .cooldown_until
+                    .filter(|until| *until > now)
+                    .map(|until| (until - now).as_secs()),
+            })
+            .collect()
+    }
+}
+
+// 401/403 invalid key, 429 rate limited, 432/433 plan or pay-as-you-go quota exceeded
+fn is_key_failure(status: StatusCode) -> bool {
+    matches!(status.as_u16(), 401 | 403 | 429 | 432 | 433)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn pool(strategy: KeyStrategy, cooldown: Duration) -> KeyPool {
+        let keys = ["a:tvly-a", "b:tvly-b", "c:tvly-c"]
+            .iter()
+            .enumerate()
+            .map(|(index, entry)| ApiKey::parse(entry, index))
+            .collect();
+        KeyPool::new(keys, strategy, cooldown)
+    }
+
+    fn next(pool: &KeyPool) -> &str {
+        &pool.select().unwrap().1.label
+    }
+
+    #[test]
+    fn keys_are_labelled_by_prefix_or_position() {
+        let key = ApiKey::parse(" backup : tvly-secret ", 0);
+        assert_eq!(
+            (key.label.as_str(), key.secret.as_str()),
+            ("backup", "tvly-secret")
+        );
+
+        let key = ApiKey::parse("tvly-secret", 1);
+        assert_eq!(
+            (key.label.as_str(), key.secret.as_str()),
+            ("key-2", "tvly-secret")
+        );
+        assert!(!format!("{:?}", key).contains("tvly-secret"));
+    }
+
+    #[test]
+    fn round_robin_rotates_through_available_keys() {
+        let pool = pool(KeyStrategy::RoundRobin, Duration::from_secs(60));
+        let labels: Vec<String> = (0..4).map(|_| next(&pool).to_string()).collect();
+        assert_eq!(labels, ["a", "b", "c", "a"]);
+
+        assert!(pool.report_failure(1, StatusCode::TOO_MANY_REQUESTS));
+        let labels: Vec<String> = (0..3).map(|_| next(&pool).to_string()).collect();
+        assert_eq!(labels, ["c", "a", "c"]);
+    }
+
+    #[test]
+    fn least_used_picks_the_key_with_fewest_requests() {
+        let pool = pool(KeyStrategy::LeastUsed, Duration::from_secs(60));
+        assert_eq!(next(&pool), "a");
+        assert_eq!(next(&pool), "b");
+        assert_eq!(next(&pool), "c");
+
+        pool.report_failure(0, StatusCode::UNAUTHORIZED);
+        assert_eq!(next(&pool), "b");
+        assert_eq!(next(&pool), "c");
+    }
+
+    #[test]
+    fn failover_uses_the_first_available_key() {
+        let pool = pool(KeyStrategy::Failover, Duration::from_secs(60));
+        assert_eq!(next(&pool), "a");
+        assert_eq!(next(&pool), "a");
+
+        assert!(pool.report_failure(0, StatusCode::from_u16(432).unwrap()));
+        assert_eq!(next(&pool), "b");
+    }
+
+    #[test]
+    fn only_key_failures_start_a_cooldown() {
+        let pool = pool(KeyStrategy::Failover, Duration::from_secs(60));
+
+        for status in [StatusCode::BAD_REQUEST, StatusCode::INTERNAL_SERVER_ERROR] {
+            assert!(!pool.report_failure(0, status));
+        }
+        assert_eq!(next(&pool), "a");
+        assert!(pool.status().iter().all(|key| key.failures == 0));
+    }
+
+    #[test]
+    fn keys_return_after_their_cooldown() {
+        let pool = pool(KeyStrategy::Failover, Duration::ZERO);
+
+        pool.report_failure(0, StatusCode::FORBIDDEN);
+        assert_eq!(pool.status()[0].failures, 1);
+        assert_eq!(next(&pool), "a");
+        assert!(pool.status()[0].cooldown_remaining_secs.is_none());
+    }
+
+    #[test]
+    fn the_key_that_recovers_first_is_used_when_all_cool_down() {
+        let pool = pool(KeyStrategy::RoundRobin, Duration::from_secs(60));
+
+        assert!(pool.report_failure(2, StatusCode::TOO_MANY_REQUESTS));
+        assert!(pool.report_failure(0, StatusCode::TOO_MANY_REQUESTS));
+        assert!(!pool.report_failure(1, StatusCode::TOO_MANY_REQUESTS));
+
+        assert_eq!(next(&pool), "c");
+        assert!(pool.status()[2].cooldown_remaining_secs.is_some());
+    }
+
+    #[test]
+    fn client_keys_are_taken_from_meta_by_policy() {
+        let mut params = json!({ "query": "rust", "_meta": { "tavily_api_key": " tvly-own " } });
+        let key = take_client_key(&mut params, ClientKeyPolicy::Allow)
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            (key.label.as_str(), key.secret.as_str()),
+            ("client", "tvly-own")
+        );
+        assert!(params["_meta"].get(CLIENT_KEY_FIELD).is_none());
+
+        let mut params = json!({ "_meta": { "tavily_api_key": "tvly-own" } });
+        assert!(take_client_key(&mut params, ClientKeyPolicy::Forbid).is_err());
+        assert!(take_client_key(&mut json!({}), ClientKeyPolicy::Require).is_err());
+        assert!(
+            take_client_key(&mut json!({}), ClientKeyPolicy::Allow)
+                .unwrap()
+                .is_none()
+        );
+    }
+}
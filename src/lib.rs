@@ -0,0 +1,120 @@
+//! Tavily search and extract tools for `anet_mcp_server`.
+//!
+//! The `tavily-anet-mcp` binary is a thin wrapper around this crate. Other
+//! services can reuse its pieces directly:
+//!
+//! - [`client::TavilyClient`] calls the Tavily API with retries, key rotation,
+//!   rate limiting and circuit breakers.
+//! - [`models::tavily`] holds the Tavily request and response types.
+//! - [`tools`] contains the MCP tools, and [`tools::registry::build_tools`]
+//!   builds the ones enabled in a [`config::Config`].
+//! - [`utils::formatter`] renders responses as the text the tools return.
+//! - [`server::McpServer`] answers MCP requests with the tools, and
+//!   [`transport`] carries them, e.g. over NATS.
+//! - [`mcp_client::TavilyMcpClient`] calls those tools on a running server over
+//!   NATS, stdio or HTTP, or on an in-process `McpServer`, and returns typed
+//!   responses.
+//!
+//! ```no_run
+//! use std::sync::Arc;
+//! use tavily_anet_mcp::client::TavilyClient;
+//! use tavily_anet_mcp::config::Config;
+//! use tavily_anet_mcp::models::tavily::TavilyResponse;
+//! use tavily_anet_mcp::utils::formatter::format_tavily_results;
+//!
+//! # async fn example() -> anyhow::Result<()> {
+//! let config = Config::from_file("config.toml".as_ref())?;
+//! let client = Arc::new(TavilyClient::new(
+//!     config.api_keys()?,
+//!     &config.tavily,
+//!     &config.retry,
+//!     &config.rate_limit,
+//!     &config.circuit_breaker,
+//! )?);
+//!
+//! let response: TavilyResponse = client
+//!     .post("search", &serde_json::json!({ "query": "rust async runtimes" }))
+//!     .await?;
+//! println!("{}", format_tavily_results(&response));
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! # Features
+//!
+//! - `nats` (default): the NATS transport and `serve` command, the health
+//!   responder, the JetStream job queue, the NATS KV cache and the NATS audit sink.
+//! - `http` (default): the HTTP admin server for `/metrics`, `/healthz` and
+//!   `/readyz`. It reports the health of `serve`, so it requires `nats`.
+//! - `jwt` (default): JWT client authentication with `jsonwebtoken`.
+//! - `otlp` (default): span export to an OTLP collector over gRPC.
+//!
+//! Without them the tools, client, models and formatters still work, with
+//! the in-memory cache and file audit sink. `reqwest` is always required.
+
+#[cfg(feature = "http")]
+pub mod admin;
+pub mod audit;
+pub mod auth;
+pub mod cache;
+pub mod cli;
+pub mod client;
+mod commands;
+pub mod config;
+#[cfg(feature = "nats")]
+pub mod health;
+#[cfg(feature = "nats")]
+pub mod jobs;
+pub mod keys;
+pub mod mcp_client;
+pub mod metrics;
+pub mod models;
+pub mod server;
+pub mod shutdown;
+pub mod telemetry;
+pub mod tools;
+pub mod transport;
+pub mod usage;
+pub mod utils;
+
+use anyhow::Result;
+use tracing::warn;
+
+use crate::cli::{Cli, Command};
+use crate::config::Config;
+
+/// Runs the command line: loads the configuration, sets up logging and
+/// tracing, and runs the selected subcommand (`serve` by default).
+pub async fn run(cli: Cli) -> Result<()> {
+    let mut config = Config::load(&cli)?;
+    let warnings = config.take_warnings();
+
+    if cli.print_config {
+        for warning in &warnings {
+            eprintln!("Warning: {}", warning);
+        }
+        print!("{}", config.redacted().to_toml()?);
+        return Ok(());
+    }
+
+    telemetry::init(&config.logging, &config.tracing, config.secrets())?;
+    for warning in warnings {
+        warn!("{}", warning);
+    }
+
+    let result = match cli.command.unwrap_or(Command::Serve) {
+        #[cfg(feature = "nats")]
+        Command::Serve => commands::serve::run(config).await,
+        #[cfg(not(feature = "nats"))]
+        Command::Serve => Err(anyhow::anyhow!(
+            "`serve` needs the NATS transport, which this build excludes (enable the `nats` feature)"
+        )),
+        Command::Check { offline } => commands::check::run(&config, offline).await,
+        Command::ListTools => commands::list_tools::run(&config),
+        Command::Usage => commands::usage::run(&config).await,
+        Command::Call { tool, args } => commands::call::run(&config, &tool, &args).await,
+    };
+
+    telemetry::shutdown();
+    result
+}
@@ -0,0 +1,70 @@
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use reqwest::Client;
+use reqwest::header::{AUTHORIZATION, HeaderMap, HeaderName, HeaderValue};
+use serde_json::Value;
+
+use crate::mcp_client::{ClientTransport, response_id};
+
+// Posts each JSON-RPC request to a server's HTTP endpoint and reads the response from the body
+pub struct HttpClientTransport {
+    client: Client,
+    url: String,
+    headers: HeaderMap,
+}
+
+impl HttpClientTransport {
+    pub fn new(url: &str) -> Self {
+        Self {
+            client: Client::new(),
+            url: url.to_string(),
+            headers: HeaderMap::new(),
+        }
+    }
+
+    pub fn with_header(mut self, name: &str, value: &str) -> Result<Self> {
+        self.headers.insert(
+            HeaderName::from_bytes(name.as_bytes())?,
+            HeaderValue::from_str(value)?,
+        );
+        Ok(self)
+    }
+
+    pub fn with_bearer_token(self, token: &str) -> Result<Self> {
+        self.with_header(AUTHORIZATION.as_str(), &format!("Bearer {}", token))
+    }
+}
+
+#[async_trait]
+impl ClientTransport for HttpClientTransport {
+    async fn request(&self, id: &str, request: Value) -> Result<Value> {
+        let response = self
+            .client
+            .post(&self.url)
+            .headers(self.headers.clone())
+            .json(&request)
+            .send()
+            .await
+            .with_context(|| format!("Failed to reach MCP server at {}", self.url))?;
+
+        // JSON-RPC errors may come with a 4xx/5xx status, so the body decides
+        let status = response.status();
+        let body = response.text().await?;
+        let response: Value = serde_json::from_str(&body).with_context(|| {
+            format!(
+                "MCP server answered HTTP {} without a JSON-RPC response",
+                status
+            )
+        })?;
+
+        // Each request has its own HTTP exchange, so another id means a confused server
+        match response_id(&response) {
+            Some(response_id) if response_id == id => Ok(response),
+            other => Err(anyhow::anyhow!(
+                "MCP server answered request {} with response {}",
+                id,
+                other.as_deref().unwrap_or("without id")
+            )),
+        }
+    }
+}
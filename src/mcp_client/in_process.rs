@@ -0,0 +1,42 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use serde_json::Value;
+use std::collections::HashMap;
+
+use crate::mcp_client::ClientTransport;
+use crate::server::{self, McpServer};
+use crate::transport::meta_from_headers;
+
+// Hands requests straight to an `McpServer` in the same process, the way a
+// server transport would, for embedding the tools or testing without a broker
+pub struct InProcessTransport {
+    server: McpServer,
+    headers: HashMap<String, String>,
+}
+
+impl InProcessTransport {
+    pub fn new(server: McpServer) -> Self {
+        Self {
+            server,
+            headers: HashMap::new(),
+        }
+    }
+
+    /// Sends a request header with every request, such as `Authorization`.
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.headers.insert(name.to_string(), value.to_string());
+        self
+    }
+}
+
+#[async_trait]
+impl ClientTransport for InProcessTransport {
+    async fn request(&self, id: &str, request: Value) -> Result<Value> {
+        let meta = meta_from_headers(|name| self.headers.get(name).cloned());
+        let response = match self.server.admit() {
+            Some(_in_flight) => self.server.handle(request, meta).await,
+            None => server::shutting_down(&request),
+        };
+        response.ok_or_else(|| anyhow::anyhow!("No response to request {}", id))
+    }
+}
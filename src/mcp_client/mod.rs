@@ -0,0 +1,388 @@
+pub mod http;
+pub mod in_process;
+#[cfg(feature = "nats")]
+pub mod nats;
+pub mod stdio;
+
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value, json};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::oneshot;
+use tracing::debug;
+
+use crate::models::tavily::{
+    TavilyExtractRequest, TavilyExtractResponse, TavilyResponse, TavilySearchRequest,
+};
+use crate::utils::response_format::RESPONSE_FORMAT_FIELD;
+
+pub use crate::mcp_client::http::HttpClientTransport;
+pub use crate::mcp_client::in_process::InProcessTransport;
+#[cfg(feature = "nats")]
+pub use crate::mcp_client::nats::NatsClientTransport;
+pub use crate::mcp_client::stdio::StdioClientTransport;
+
+/// Carries JSON-RPC requests to an MCP server and brings back the response
+/// with the same `id`.
+#[async_trait]
+pub trait ClientTransport: Send + Sync {
+    async fn request(&self, id: &str, request: Value) -> anyhow::Result<Value>;
+}
+
+#[derive(Debug)]
+pub enum McpClientError {
+    /// No response arrived within the client's timeout.
+    Timeout { method: String, after: Duration },
+    /// The request could not be sent or the response could not be received.
+    Transport(anyhow::Error),
+    /// The server answered with a JSON-RPC error.
+    Rpc {
+        code: i64,
+        message: String,
+        data: Option<Value>,
+    },
+    /// The tool ran and failed, e.g. because Tavily rejected the request.
+    Tool { tool: String, message: String },
+    /// The tool arguments could not be built.
+    InvalidArguments(String),
+    /// The response did not have the expected shape.
+    InvalidResponse(String),
+}
+
+impl fmt::Display for McpClientError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            McpClientError::Timeout { method, after } => {
+                write!(f, "No response to {} within {:?}", method, after)
+            }
+            McpClientError::Transport(e) => write!(f, "Transport error: {:#}", e),
+            McpClientError::Rpc { code, message, .. } => {
+                write!(f, "Server error {}: {}", code, message)
+            }
+            McpClientError::Tool { tool, message } => write!(f, "{} failed: {}", tool, message),
+            McpClientError::InvalidArguments(reason) => {
+                write!(f, "Invalid tool arguments: {}", reason)
+            }
+            McpClientError::InvalidResponse(reason) => {
+                write!(f, "Invalid response from server: {}", reason)
+            }
+        }
+    }
+}
+
+impl std::error::Error for McpClientError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            McpClientError::Transport(e) => Some(e.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+pub type Result<T> = std::result::Result<T, McpClientError>;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ToolInfo {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default, rename = "inputSchema")]
+    pub input_schema: Value,
+}
+
+// Typed client for the Tavily tools served by this crate, over any `ClientTransport`
+pub struct TavilyMcpClient {
+    transport: Box<dyn ClientTransport>,
+    timeout: Duration,
+    next_id: AtomicU64,
+    meta: Map<String, Value>,
+}
+
+impl TavilyMcpClient {
+    pub fn new(transport: impl ClientTransport + 'static) -> Self {
+        let mut meta = Map::new();
+        meta.insert(
+            "clientInfo".to_string(),
+            json!({ "name": "tavily-mcp-client", "version": env!("CARGO_PKG_VERSION") }),
+        );
+
+        Self {
+            transport: Box::new(transport),
+            timeout: Duration::from_secs(30),
+            next_id: AtomicU64::new(1),
+            meta,
+        }
+    }
+
+    /// Connects to a server listening on a NATS subject, e.g. `mcp.requests`.
+    #[cfg(feature = "nats")]
+    pub async fn connect_nats(nats_url: &str, subject: &str) -> anyhow::Result<Self> {
+        Ok(Self::new(
+            NatsClientTransport::connect(nats_url, subject).await?,
+        ))
+    }
+
+    /// How long to wait for each response. Defaults to 30 seconds.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Identifies the client in the server's audit log and usage accounting.
+    pub fn with_client_info(mut self, name: &str, version: &str) -> Self {
+        self.meta.insert(
+            "clientInfo".to_string(),
+            json!({ "name": name, "version": version }),
+        );
+        self
+    }
+
+    /// Adds a field to the `_meta` argument of every tool call, such as
+    /// `authorization`, `client_id` or `tavily_api_key`.
+    pub fn with_meta(mut self, key: &str, value: impl Into<Value>) -> Self {
+        self.meta.insert(key.to_string(), value.into());
+        self
+    }
+
+    /// Sends a JSON-RPC request and returns its `result`.
+    pub async fn request(&self, method: &str, params: Value) -> Result<Value> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed).to_string();
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": method,
+            "params": params,
+        });
+        debug!("Sending MCP request {} ({})", id, method);
+
+        let response = tokio::time::timeout(self.timeout, self.transport.request(&id, request))
+            .await
+            .map_err(|_| McpClientError::Timeout {
+                method: method.to_string(),
+                after: self.timeout,
+            })?
+            .map_err(McpClientError::Transport)?;
+
+        if let Some(error) = response.get("error").filter(|e| !e.is_null()) {
+            return Err(McpClientError::Rpc {
+                code: error.get("code").and_then(|c| c.as_i64()).unwrap_or(0),
+                message: error
+                    .get("message")
+                    .and_then(|m| m.as_str())
+                    .unwrap_or("Unknown error")
+                    .to_string(),
+                data: error.get("data").cloned(),
+            });
+        }
+
+        response
+            .get("result")
+            .cloned()
+            .ok_or_else(|| McpClientError::InvalidResponse(format!("no result in {}", response)))
+    }
+
+    pub async fn initialize(&self) -> Result<Value> {
+        let client_info = self.meta.get("clientInfo").cloned().unwrap_or(Value::Null);
+        self.request("initialize", json!({ "clientInfo": client_info }))
+            .await
+    }
+
+    pub async fn list_tools(&self) -> Result<Vec<ToolInfo>> {
+        let result = self.request("listTools", json!({})).await?;
+        let tools = result.get("tools").cloned().unwrap_or(result);
+        serde_json::from_value(tools).map_err(|e| McpClientError::InvalidResponse(e.to_string()))
+    }
+
+    /// Calls a tool and returns the text of its result.
+    pub async fn call_tool(&self, tool: &str, arguments: Value) -> Result<String> {
+        let mut arguments = match arguments {
+            Value::Object(arguments) => arguments,
+            Value::Null => Map::new(),
+            other => {
+                return Err(McpClientError::InvalidArguments(format!(
+                    "expected an object, got {}",
+                    other
+                )));
+            }
+        };
+        let meta = arguments
+            .entry("_meta")
+            .or_insert_with(|| json!({}))
+            .as_object_mut()
+            .ok_or_else(|| McpClientError::InvalidArguments("_meta must be an object".into()))?;
+        for (key, value) in &self.meta {
+            meta.entry(key.clone()).or_insert_with(|| value.clone());
+        }
+
+        let result = self
+            .request("callTool", json!({ "name": tool, "arguments": arguments }))
+            .await
+            .map_err(|e| match e {
+                // Tool failures come back as JSON-RPC errors from the transport
+                McpClientError::Rpc { message, .. } => McpClientError::Tool {
+                    tool: tool.to_string(),
+                    message,
+                },
+                e => e,
+            })?;
+
+        let text = content_text(&result);
+        if result.get("isError").and_then(|e| e.as_bool()) == Some(true) {
+            return Err(McpClientError::Tool {
+                tool: tool.to_string(),
+                message: text,
+            });
+        }
+        Ok(text)
+    }
+
+    pub async fn search(&self, request: &TavilySearchRequest) -> Result<TavilyResponse> {
+        self.call_typed("tavily-search", request).await
+    }
+
+    pub async fn extract(&self, request: &TavilyExtractRequest) -> Result<TavilyExtractResponse> {
+        self.call_typed("tavily-extract", request).await
+    }
+
+    /// Returns the usage report: Tavily's usage for each of the server's keys,
+    /// their request counts and cooldowns and the credits spent through the server.
+    pub async fn usage(&self) -> Result<Value> {
+        let text = self.call_tool("tavily-usage", json!({})).await?;
+        parse(&text)
+    }
+
+    // Asks the tool for the JSON response instead of its text summary
+    async fn call_typed<T: DeserializeOwned>(
+        &self,
+        tool: &str,
+        request: &impl serde::Serialize,
+    ) -> Result<T> {
+        let mut arguments = serde_json::to_value(request)
+            .map_err(|e| McpClientError::InvalidArguments(e.to_string()))?;
+        arguments["_meta"] = json!({ RESPONSE_FORMAT_FIELD: "json" });
+
+        let text = self.call_tool(tool, arguments).await?;
+        parse(&text)
+    }
+}
+
+fn parse<T: DeserializeOwned>(text: &str) -> Result<T> {
+    serde_json::from_str(text)
+        .map_err(|e| McpClientError::InvalidResponse(format!("{} in {}", e, truncate(text, 200))))
+}
+
+fn truncate(text: &str, max_chars: usize) -> String {
+    match text.char_indices().nth(max_chars) {
+        Some((end, _)) => format!("{}...", &text[..end]),
+        None => text.to_string(),
+    }
+}
+
+/// Joins the text parts of a `callTool` result.
+fn content_text(result: &Value) -> String {
+    result
+        .get("content")
+        .and_then(|content| content.as_array())
+        .map(|parts| {
+            parts
+                .iter()
+                .filter_map(|part| part.get("text").and_then(|t| t.as_str()))
+                .collect::<Vec<_>>()
+                .join("\n")
+        })
+        .unwrap_or_default()
+}
+
+/// Requests waiting for a response, for transports that share one reply
+/// channel between all requests.
+#[derive(Clone)]
+pub struct Pending {
+    // `None` once the reply channel is gone
+    waiting: Arc<Mutex<Option<Waiting>>>,
+}
+
+type Waiting = HashMap<String, oneshot::Sender<Value>>;
+
+impl Default for Pending {
+    fn default() -> Self {
+        Self {
+            waiting: Arc::new(Mutex::new(Some(HashMap::new()))),
+        }
+    }
+}
+
+impl Pending {
+    /// Registers `id`. The registration is removed when the returned waiter is
+    /// dropped, so requests that time out don't leak. After [`Pending::close`]
+    /// the waiter fails at once.
+    pub fn register(&self, id: &str) -> Waiter {
+        let (sender, receiver) = oneshot::channel();
+        if let Some(waiting) = self.waiting.lock().unwrap().as_mut() {
+            waiting.insert(id.to_string(), sender);
+        }
+        Waiter {
+            id: id.to_string(),
+            receiver,
+            pending: self.clone(),
+        }
+    }
+
+    /// Hands a response to the request waiting for `id`. Responses nobody is
+    /// waiting for, e.g. after a timeout, are dropped.
+    pub fn complete(&self, id: &str, response: Value) {
+        let sender = self
+            .waiting
+            .lock()
+            .unwrap()
+            .as_mut()
+            .and_then(|waiting| waiting.remove(id));
+        match sender {
+            Some(sender) => {
+                let _ = sender.send(response);
+            }
+            None => debug!("Dropping MCP response {} nobody is waiting for", id),
+        }
+    }
+
+    /// Fails every waiting request and any registered later, e.g. when the
+    /// connection is gone.
+    pub fn close(&self) {
+        self.waiting.lock().unwrap().take();
+    }
+}
+
+pub struct Waiter {
+    id: String,
+    receiver: oneshot::Receiver<Value>,
+    pending: Pending,
+}
+
+impl Waiter {
+    pub async fn response(mut self) -> anyhow::Result<Value> {
+        (&mut self.receiver)
+            .await
+            .map_err(|_| anyhow::anyhow!("Connection closed before response {}", self.id))
+    }
+}
+
+impl Drop for Waiter {
+    fn drop(&mut self) {
+        if let Some(waiting) = self.pending.waiting.lock().unwrap().as_mut() {
+            waiting.remove(&self.id);
+        }
+    }
+}
+
+/// Reads the `id` of a JSON-RPC response, which servers may send as a string or a number.
+fn response_id(response: &Value) -> Option<String> {
+    match response.get("id")? {
+        Value::String(id) => Some(id.clone()),
+        Value::Number(id) => Some(id.to_string()),
+        _ => None,
+    }
+}
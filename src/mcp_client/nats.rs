@@ -0,0 +1,80 @@
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use futures_util::StreamExt;
+use serde_json::Value;
+use tokio::task::JoinHandle;
+use tracing::{debug, warn};
+
+use crate::mcp_client::{ClientTransport, Pending};
+
+// Sends requests to a server's NATS subject. Replies to every request arrive on
+// one inbox subscription, addressed as `<inbox>.<request id>`
+pub struct NatsClientTransport {
+    client: async_nats::Client,
+    subject: String,
+    inbox: String,
+    pending: Pending,
+    router: JoinHandle<()>,
+}
+
+impl NatsClientTransport {
+    pub async fn connect(nats_url: &str, subject: &str) -> Result<Self> {
+        let client = async_nats::connect(nats_url)
+            .await
+            .with_context(|| format!("Failed to connect to NATS at {}", nats_url))?;
+        Self::new(client, subject).await
+    }
+
+    /// Uses an existing connection, which may be shared with other code.
+    pub async fn new(client: async_nats::Client, subject: &str) -> Result<Self> {
+        let inbox = client.new_inbox();
+        let mut replies = client.subscribe(format!("{}.*", inbox)).await?;
+        debug!("Receiving MCP replies on {}.*", inbox);
+
+        let pending = Pending::default();
+        let routed = pending.clone();
+        let router = tokio::spawn(async move {
+            while let Some(reply) = replies.next().await {
+                let reply_subject = reply.subject.to_string();
+                let Some((_, id)) = reply_subject.rsplit_once('.') else {
+                    continue;
+                };
+                match serde_json::from_slice(&reply.payload) {
+                    Ok(response) => routed.complete(id, response),
+                    Err(e) => warn!("Ignoring malformed MCP reply on {}: {}", reply_subject, e),
+                }
+            }
+            routed.close();
+        });
+
+        Ok(Self {
+            client,
+            subject: subject.to_string(),
+            inbox,
+            pending,
+            router,
+        })
+    }
+}
+
+#[async_trait]
+impl ClientTransport for NatsClientTransport {
+    async fn request(&self, id: &str, request: Value) -> Result<Value> {
+        let waiter = self.pending.register(id);
+        self.client
+            .publish_with_reply(
+                self.subject.clone(),
+                format!("{}.{}", self.inbox, id),
+                serde_json::to_vec(&request)?.into(),
+            )
+            .await
+            .with_context(|| format!("Failed to publish to {}", self.subject))?;
+        waiter.response().await
+    }
+}
+
+impl Drop for NatsClientTransport {
+    fn drop(&mut self) {
+        self.router.abort();
+    }
+}
@@ -0,0 +1,100 @@
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use serde_json::Value;
+use std::process::Stdio;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::process::{Child, ChildStdin, Command};
+use tokio::sync::Mutex;
+use tokio::task::JoinHandle;
+use tracing::{debug, warn};
+
+use crate::mcp_client::{ClientTransport, Pending, response_id};
+
+// Talks to a server running as a child process, one JSON-RPC message per line
+pub struct StdioClientTransport {
+    stdin: Mutex<ChildStdin>,
+    pending: Pending,
+    reader: JoinHandle<()>,
+    _child: Child,
+}
+
+impl StdioClientTransport {
+    /// Spawns `command` with piped stdin and stdout. The process is killed when
+    /// the transport is dropped.
+    pub fn spawn(mut command: Command) -> Result<Self> {
+        let mut child = command
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .context("Failed to start MCP server process")?;
+        let stdin = child.stdin.take().context("Server stdin is not piped")?;
+        let stdout = child.stdout.take().context("Server stdout is not piped")?;
+
+        let pending = Pending::default();
+        let routed = pending.clone();
+        let reader = tokio::spawn(async move {
+            let mut lines = BufReader::new(stdout).lines();
+            loop {
+                let line = match lines.next_line().await {
+                    Ok(Some(line)) => line,
+                    Ok(None) => break,
+                    Err(e) => {
+                        warn!("Failed to read from MCP server: {}", e);
+                        break;
+                    }
+                };
+                if line.trim().is_empty() {
+                    continue;
+                }
+
+                let response: Value = match serde_json::from_str(&line) {
+                    Ok(response) => response,
+                    Err(e) => {
+                        warn!("Ignoring malformed line from MCP server: {}", e);
+                        continue;
+                    }
+                };
+                // Notifications have no id and nobody waits for them
+                match response_id(&response) {
+                    Some(id) => routed.complete(&id, response),
+                    None => debug!("Ignoring MCP message without id"),
+                }
+            }
+            debug!("MCP server closed its output");
+            routed.close();
+        });
+
+        Ok(Self {
+            stdin: Mutex::new(stdin),
+            pending,
+            reader,
+            _child: child,
+        })
+    }
+}
+
+#[async_trait]
+impl ClientTransport for StdioClientTransport {
+    async fn request(&self, id: &str, request: Value) -> Result<Value> {
+        let waiter = self.pending.register(id);
+
+        let mut line = serde_json::to_vec(&request)?;
+        line.push(b'\n');
+        let mut stdin = self.stdin.lock().await;
+        stdin
+            .write_all(&line)
+            .await
+            .context("Failed to write to MCP server")?;
+        stdin.flush().await?;
+        drop(stdin);
+
+        waiter.response().await
+    }
+}
+
+impl Drop for StdioClientTransport {
+    fn drop(&mut self) {
+        self.reader.abort();
+    }
+}
@@ -0,0 +1,273 @@
+use prometheus::{
+    Encoder, HistogramOpts, HistogramVec, IntCounterVec, IntGauge, IntGaugeVec, Opts, Registry,
+    TextEncoder,
+};
+use std::sync::LazyLock;
+use std::time::Duration;
+
+// Prometheus metrics for the server, registered in a dedicated registry
+pub struct Metrics {
+    registry: Registry,
+    tool_calls: IntCounterVec,
+    tool_duration: HistogramVec,
+    in_flight: IntGauge,
+    upstream_duration: HistogramVec,
+    upstream_retries: IntCounterVec,
+    cache_requests: IntCounterVec,
+    rate_limiter_wait: HistogramVec,
+    credits: IntCounterVec,
+    key_requests: IntCounterVec,
+    key_failovers: IntCounterVec,
+    key_available: IntGaugeVec,
+    auth_rejections: IntCounterVec,
+    breaker_state: IntGaugeVec,
+    breaker_rejections: IntCounterVec,
+}
+
+static METRICS: LazyLock<Metrics> = LazyLock::new(Metrics::new);
+
+pub fn metrics() -> &'static Metrics {
+    &METRICS
+}
+
+impl Metrics {
+    fn new() -> Self {
+        let registry = Registry::new_custom(Some("tavily_mcp".to_string()), None)
+            .expect("metric prefix is valid");
+
+        let tool_calls = IntCounterVec::new(
+            Opts::new("tool_calls_total", "Tool calls by tool and outcome"),
+            &["tool", "outcome"],
+        )
+        .unwrap();
+        let tool_duration = HistogramVec::new(
+            HistogramOpts::new("tool_call_duration_seconds", "Tool call latency by tool"),
+            &["tool"],
+        )
+        .unwrap();
+        let in_flight =
+            IntGauge::new("in_flight_requests", "Tool calls currently in progress").unwrap();
+        let upstream_duration = HistogramVec::new(
+            HistogramOpts::new(
+                "upstream_request_duration_seconds",
+                "Tavily API latency by endpoint and status code",
+            ),
+            &["endpoint", "status"],
+        )
+        .unwrap();
+        let upstream_retries = IntCounterVec::new(
+            Opts::new(
+                "upstream_retries_total",
+                "Retried Tavily API requests by endpoint",
+            ),
+            &["endpoint"],
+        )
+        .unwrap();
+        let cache_requests = IntCounterVec::new(
+            Opts::new(
+                "cache_requests_total",
+                "Cache lookups by backend and result",
+            ),
+            &["backend", "result"],
+        )
+        .unwrap();
+        let rate_limiter_wait = HistogramVec::new(
+            HistogramOpts::new(
+                "rate_limiter_wait_seconds",
+                "Time spent waiting for the upstream rate limiter",
+            )
+            .buckets(vec![0.0, 0.01, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0]),
+            &["endpoint"],
+        )
+        .unwrap();
+        let credits = IntCounterVec::new(
+            Opts::new(
+                "credits_estimated_total",
+                "Estimated Tavily credits consumed by endpoint",
+            ),
+            &["endpoint"],
+        )
+        .unwrap();
+
+        let key_requests = IntCounterVec::new(
+            Opts::new(
+                "api_key_requests_total",
+                "Tavily API requests by key label and status code",
+            ),
+            &["key", "status"],
+        )
+        .unwrap();
+        let key_failovers = IntCounterVec::new(
+            Opts::new(
+                "api_key_failovers_total",
+                "Requests moved to another key after this key was rejected",
+            ),
+            &["key"],
+        )
+        .unwrap();
+        let key_available = IntGaugeVec::new(
+            Opts::new(
+                "api_key_available",
+                "1 when the key is in rotation, 0 while it is cooling down",
+            ),
+            &["key"],
+        )
+        .unwrap();
+
+        let auth_rejections = IntCounterVec::new(
+            Opts::new(
+                "auth_rejections_total",
+                "Tool calls rejected by client authentication or policy",
+            ),
+            &["reason"],
+        )
+        .unwrap();
+
+        let breaker_state = IntGaugeVec::new(
+            Opts::new(
+                "circuit_breaker_state",
+                "Circuit breaker state by endpoint: 0 closed, 1 half-open, 2 open",
+            ),
+            &["endpoint"],
+        )
+        .unwrap();
+        let breaker_rejections = IntCounterVec::new(
+            Opts::new(
+                "circuit_breaker_rejections_total",
+                "Requests failed fast by an open circuit breaker",
+            ),
+            &["endpoint"],
+        )
+        .unwrap();
+
+        for collector in [
+            Box::new(tool_calls.clone()) as Box<dyn prometheus::core::Collector>,
+            Box::new(tool_duration.clone()),
+            Box::new(in_flight.clone()),
+            Box::new(upstream_duration.clone()),
+            Box::new(upstream_retries.clone()),
+            Box::new(cache_requests.clone()),
+            Box::new(rate_limiter_wait.clone()),
+            Box::new(credits.clone()),
+            Box::new(key_requests.clone()),
+            Box::new(key_failovers.clone()),
+            Box::new(key_available.clone()),
+            Box::new(auth_rejections.clone()),
+            Box::new(breaker_state.clone()),
+            Box::new(breaker_rejections.clone()),
+        ] {
+            registry
+                .register(collector)
+                .expect("metric names are unique");
+        }
+
+        Self {
+            registry,
+            tool_calls,
+            tool_duration,
+            in_flight,
+            upstream_duration,
+            upstream_retries,
+            cache_requests,
+            rate_limiter_wait,
+            credits,
+            key_requests,
+            key_failovers,
+            key_available,
+            auth_rejections,
+            breaker_state,
+            breaker_rejections,
+        }
+    }
+
+    /// Marks a tool call as in flight until the returned guard is dropped.
+    pub fn start_tool_call(&self) -> InFlightGuard<'_> {
+        self.in_flight.inc();
+        InFlightGuard {
+            gauge: &self.in_flight,
+        }
+    }
+
+    pub fn record_tool_call(&self, tool: &str, success: bool, elapsed: Duration) {
+        let outcome = if success { "success" } else { "error" };
+        self.tool_calls.with_label_values(&[tool, outcome]).inc();
+        self.tool_duration
+            .with_label_values(&[tool])
+            .observe(elapsed.as_secs_f64());
+    }
+
+    /// `status` is the HTTP status code, or `error` when no response was received.
+    pub fn record_upstream(&self, endpoint: &str, status: &str, elapsed: Duration) {
+        self.upstream_duration
+            .with_label_values(&[endpoint, status])
+            .observe(elapsed.as_secs_f64());
+    }
+
+    pub fn record_retry(&self, endpoint: &str) {
+        self.upstream_retries.with_label_values(&[endpoint]).inc();
+    }
+
+    /// `result` is `hit`, `miss` or `stale`.
+    pub fn record_cache(&self, backend: &str, result: &str) {
+        self.cache_requests
+            .with_label_values(&[backend, result])
+            .inc();
+    }
+
+    pub fn record_rate_limiter_wait(&self, endpoint: &str, waited: Duration) {
+        self.rate_limiter_wait
+            .with_label_values(&[endpoint])
+            .observe(waited.as_secs_f64());
+    }
+
+    pub fn record_credits(&self, endpoint: &str, credits: u64) {
+        self.credits.with_label_values(&[endpoint]).inc_by(credits);
+    }
+
+    /// `key` is the key's label, never the key itself.
+    pub fn record_key_request(&self, key: &str, status: &str) {
+        self.key_requests.with_label_values(&[key, status]).inc();
+    }
+
+    pub fn record_key_failover(&self, key: &str) {
+        self.key_failovers.with_label_values(&[key]).inc();
+    }
+
+    pub fn set_key_available(&self, key: &str, available: bool) {
+        self.key_available
+            .with_label_values(&[key])
+            .set(i64::from(available));
+    }
+
+    /// `reason` is `unauthenticated`, `forbidden` or `rate_limited`.
+    pub fn record_auth_rejection(&self, reason: &str) {
+        self.auth_rejections.with_label_values(&[reason]).inc();
+    }
+
+    pub fn set_breaker_state(&self, endpoint: &str, state: i64) {
+        self.breaker_state.with_label_values(&[endpoint]).set(state);
+    }
+
+    pub fn record_breaker_rejection(&self, endpoint: &str) {
+        self.breaker_rejections.with_label_values(&[endpoint]).inc();
+    }
+
+    /// Renders all metrics in the Prometheus text exposition format.
+    pub fn render(&self) -> String {
+        let mut buffer = Vec::new();
+        if let Err(e) = TextEncoder::new().encode(&self.registry.gather(), &mut buffer) {
+            tracing::warn!("Failed to encode metrics: {}", e);
+        }
+        String::from_utf8(buffer).unwrap_or_default()
+    }
+}
+
+pub struct InFlightGuard<'a> {
+    gauge: &'a IntGauge,
+}
+
+impl Drop for InFlightGuard<'_> {
+    fn drop(&mut self) {
+        self.gauge.dec();
+    }
+}
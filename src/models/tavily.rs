@@ -1,57 +1,259 @@
-use serde::Deserialize;
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::{Map, Value};
 
-#[derive(Debug, Deserialize)]
+// Response models accept anything Tavily may omit or send as `null` and keep
+// fields they don't know about in `extra`, so an API change degrades the output
+// instead of failing deserialization. `extra` is serialized back flat, e.g. in
+// JSON tool output.
+
+/// Reads `null` like a missing field, for fields that aren't `Option`s.
+fn null_as_default<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Default + Deserialize<'de>,
+{
+    Ok(Option::<T>::deserialize(deserializer)?.unwrap_or_default())
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct TavilyResult {
+    #[serde(default, deserialize_with = "null_as_default")]
     pub title: String,
     pub url: String,
-    pub content: String,
-    pub score: f64,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub score: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub published_date: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub raw_content: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub favicon: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub images: Option<Vec<TavilyImage>>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(untagged)]
 pub enum TavilyImage {
     String(String),
     Object {
         url: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
         description: Option<String>,
     },
 }
 
-#[derive(Debug, Deserialize)]
+/// Credits charged for a request, returned when `include_usage` is set.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct RequestUsage {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub credits: Option<f64>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct TavilyResponse {
+    #[serde(default, deserialize_with = "null_as_default")]
     pub query: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub follow_up_questions: Option<Vec<String>>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub answer: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub images: Option<Vec<TavilyImage>>,
+    #[serde(default, deserialize_with = "null_as_default")]
     pub results: Vec<TavilyResult>,
+    /// Seconds Tavily spent on the request.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub response_time: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub usage: Option<RequestUsage>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+    /// Set by this server when Tavily was unavailable and the response came
+    /// from an expired cache entry; never sent by Tavily itself.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub stale: bool,
+    /// Unix time in milliseconds when a stale response was fetched from Tavily.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fetched_at: Option<u64>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct ExtractResult {
     pub url: String,
+    #[serde(default, deserialize_with = "null_as_default")]
     pub raw_content: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub images: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub favicon: Option<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct FailedResult {
     pub url: String,
+    #[serde(default, deserialize_with = "null_as_default")]
     pub error: String,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct TavilyExtractResponse {
+    #[serde(default, deserialize_with = "null_as_default")]
     pub results: Vec<ExtractResult>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub failed_results: Option<Vec<FailedResult>>,
-    pub response_time: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub response_time: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub usage: Option<RequestUsage>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+    /// Set by this server when Tavily was unavailable and the response came
+    /// from an expired cache entry; never sent by Tavily itself.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub stale: bool,
+    /// Unix time in milliseconds when a stale response was fetched from Tavily.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fetched_at: Option<u64>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Depth {
+    Basic,
+    Advanced,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SearchTopic {
+    General,
+    News,
+}
+
+// Arguments of the `tavily-search` tool; unset fields use the server's defaults
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct TavilySearchRequest {
+    pub query: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub search_depth: Option<Depth>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub topic: Option<SearchTopic>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub days: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub time_range: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_results: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub include_images: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub include_image_descriptions: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub include_raw_content: Option<bool>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub include_domains: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exclude_domains: Vec<String>,
+}
+
+impl TavilySearchRequest {
+    pub fn new(query: impl Into<String>) -> Self {
+        Self {
+            query: query.into(),
+            ..Self::default()
+        }
+    }
+}
+
+// Arguments of the `tavily-extract` tool
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct TavilyExtractRequest {
+    pub urls: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub extract_depth: Option<Depth>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub include_images: Option<bool>,
+}
+
+impl TavilyExtractRequest {
+    pub fn new<I, S>(urls: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self {
+            urls: urls.into_iter().map(Into::into).collect(),
+            ..Self::default()
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct KeyUsage {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub usage: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u64>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct AccountUsage {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub current_plan: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub plan_usage: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub plan_limit: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub paygo_usage: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub paygo_limit: Option<u64>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TavilyUsageResponse {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub key: Option<KeyUsage>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub account: Option<AccountUsage>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl TavilyUsageResponse {
+    /// Credits left before the key or plan limit is reached, whichever is lower.
+    pub fn remaining_credits(&self) -> Option<u64> {
+        let key = self.key.as_ref().and_then(|k| {
+            k.limit
+                .map(|limit| limit.saturating_sub(k.usage.unwrap_or(0)))
+        });
+        let plan = self.account.as_ref().and_then(|a| {
+            a.plan_limit
+                .map(|limit| limit.saturating_sub(a.plan_usage.unwrap_or(0)))
+        });
+
+        match (key, plan) {
+            (Some(key), Some(plan)) => Some(key.min(plan)),
+            (key, plan) => key.or(plan),
+        }
+    }
 }
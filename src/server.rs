@@ -0,0 +1,325 @@
+use serde_json::{Map, Value, json};
+use std::sync::Arc;
+use tracing::{debug, error};
+
+use crate::auth::Authenticator;
+use crate::shutdown::{InFlight, Shutdown};
+use crate::tools::auth::AuthorizedTool;
+use crate::tools::registry::{SharedTool, content_to_text, find_tool};
+
+/// Name the server reports to MCP clients.
+pub const SERVER_NAME: &str = "tavily-mcp";
+
+/// MCP protocol revision reported in the `initialize` result.
+pub const PROTOCOL_VERSION: &str = "2024-11-05";
+
+// JSON-RPC error codes, as `anet_mcp_server` answers them, plus a
+// server-defined code for requests refused while draining
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+const INTERNAL_ERROR: i64 = -32603;
+const SHUTTING_DOWN: i64 = -32001;
+
+// Answers MCP JSON-RPC requests with the registered tools. It serves the same
+// methods, capabilities and error codes as the `anet_mcp_server` server it
+// stands in for, whose NATS transport drops request headers. Transports hand
+// it each request along with the `_meta` fields they read from those headers.
+#[derive(Clone)]
+pub struct McpServer {
+    tools: Arc<Vec<SharedTool>>,
+    shutdown: Shutdown,
+}
+
+impl McpServer {
+    /// Serves `tools`. Requests admitted through `shutdown` can drain, and when
+    /// `auth` is set only authorized clients may call the tools.
+    pub fn new(
+        tools: Vec<SharedTool>,
+        auth: Option<Arc<Authenticator>>,
+        shutdown: &Shutdown,
+    ) -> Self {
+        let tools = tools
+            .into_iter()
+            .map(|tool| match &auth {
+                Some(auth) => Arc::new(AuthorizedTool::new(tool, auth.clone())) as SharedTool,
+                None => tool,
+            })
+            .collect();
+
+        Self {
+            tools: Arc::new(tools),
+            shutdown: shutdown.clone(),
+        }
+    }
+
+    /// Counts a request as in flight until the guard is dropped, or returns `None`
+    /// once the server is draining. Transports admit each request as soon as it
+    /// arrives, so that it is waited for even before it is handled.
+    pub fn admit(&self) -> Option<InFlight> {
+        self.shutdown.track()
+    }
+
+    pub fn tools(&self) -> &[SharedTool] {
+        &self.tools
+    }
+
+    /// Handles one JSON-RPC request and returns the response, or `None` for
+    /// notifications. `meta` is added to the `_meta` argument of tool calls
+    /// without replacing fields the caller set there.
+    pub async fn handle(&self, request: Value, meta: Map<String, Value>) -> Option<Value> {
+        let id = request.get("id").cloned();
+        let Some(method) = request.get("method").and_then(|m| m.as_str()) else {
+            return Some(error_response(
+                id.unwrap_or(Value::Null),
+                INVALID_REQUEST,
+                "Missing method",
+            ));
+        };
+        debug!("Handling MCP request {}", method);
+
+        let params = request.get("params").cloned().unwrap_or(Value::Null);
+        let result = match method {
+            "initialize" => Ok(json!({
+                "protocolVersion": PROTOCOL_VERSION,
+                "serverInfo": { "name": SERVER_NAME, "version": env!("CARGO_PKG_VERSION") },
+                "capabilities": { "tools": {}, "prompts": {}, "resources": {} },
+            })),
+            "listTools" => Ok(json!({ "tools": self.list_tools() })),
+            "callTool" => self.call_tool(params, meta).await,
+            // Advertised like the baseline server, which registered none of either
+            "listPrompts" => Ok(json!({ "prompts": [] })),
+            "listResources" => Ok(json!({ "resources": [] })),
+            "getPrompt" => Err((INVALID_PARAMS, unknown("prompt", &params, "name"))),
+            "readResource" => Err((INVALID_PARAMS, unknown("resource", &params, "uri"))),
+            "ping" => Ok(json!({})),
+            other => Err((METHOD_NOT_FOUND, format!("Method not found: {}", other))),
+        };
+
+        // Notifications carry no id and get no response
+        let id = id?;
+        Some(match result {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err((code, message)) => error_response(id, code, &message),
+        })
+    }
+
+    fn list_tools(&self) -> Vec<Value> {
+        self.tools
+            .iter()
+            .map(|tool| {
+                json!({
+                    "name": tool.name(),
+                    "description": tool.description(),
+                    "inputSchema": tool.input_schema(),
+                })
+            })
+            .collect()
+    }
+
+    async fn call_tool(
+        &self,
+        params: Value,
+        meta: Map<String, Value>,
+    ) -> Result<Value, (i64, String)> {
+        let name = params
+            .get("name")
+            .and_then(|n| n.as_str())
+            .ok_or_else(|| (INVALID_PARAMS, "Missing tool name".to_string()))?;
+        let tool = find_tool(&self.tools, name)
+            .ok_or_else(|| (INVALID_PARAMS, format!("Unknown tool: {}", name)))?;
+
+        let mut arguments = match params.get("arguments") {
+            Some(Value::Object(arguments)) => arguments.clone(),
+            None | Some(Value::Null) => Map::new(),
+            Some(_) => return Err((INVALID_PARAMS, "Arguments must be an object".to_string())),
+        };
+        if !meta.is_empty() {
+            let Value::Object(fields) = arguments.entry("_meta").or_insert_with(|| json!({}))
+            else {
+                return Err((INVALID_PARAMS, "_meta must be an object".to_string()));
+            };
+            for (key, value) in meta {
+                fields.entry(key).or_insert(value);
+            }
+        }
+
+        match tool.call(Some(Value::Object(arguments))).await {
+            Ok(content) => Ok(json!({
+                "content": [{ "type": "text", "text": content_to_text(&content) }],
+            })),
+            Err(e) => {
+                error!("Tool {} failed: {:#}", name, e);
+                Err((INTERNAL_ERROR, format!("{:#}", e)))
+            }
+        }
+    }
+}
+
+fn unknown(kind: &str, params: &Value, field: &str) -> String {
+    match params.get(field).and_then(|v| v.as_str()) {
+        Some(name) => format!("Unknown {}: {}", kind, name),
+        None => format!("Unknown {}", kind),
+    }
+}
+
+/// The response to a request that arrives while the server is draining, so the
+/// caller gets an error instead of no reply at all.
+pub fn shutting_down(request: &Value) -> Option<Value> {
+    let id = request.get("id")?.clone();
+    Some(error_response(id, SHUTTING_DOWN, "Server is shutting down"))
+}
+
+/// The response to a request that is not valid JSON.
+pub fn parse_error(error: &serde_json::Error) -> Value {
+    error_response(Value::Null, PARSE_ERROR, &format!("Parse error: {}", error))
+}
+
+fn error_response(id: Value, code: i64, message: &str) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": code, "message": message },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anet_mcp_server::{Content, Tool};
+    use async_trait::async_trait;
+    use std::time::Duration;
+
+    // Replies with the arguments it was called with
+    struct EchoTool;
+
+    #[async_trait]
+    impl Tool for EchoTool {
+        fn name(&self) -> String {
+            "echo".to_string()
+        }
+
+        fn description(&self) -> String {
+            "Echoes its arguments".to_string()
+        }
+
+        fn input_schema(&self) -> Value {
+            json!({ "type": "object" })
+        }
+
+        async fn call(&self, input: Option<Value>) -> anyhow::Result<Vec<Content>> {
+            Ok(vec![Content::Text {
+                text: input.unwrap_or_default().to_string(),
+            }])
+        }
+    }
+
+    fn server() -> McpServer {
+        McpServer::new(vec![Arc::new(EchoTool)], None, &Shutdown::new())
+    }
+
+    async fn call_echo(arguments: Value, meta: Map<String, Value>) -> Value {
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "callTool",
+            "params": { "name": "echo", "arguments": arguments },
+        });
+        let response = server().handle(request, meta).await.unwrap();
+        let text = response["result"]["content"][0]["text"].as_str().unwrap();
+        serde_json::from_str(text).unwrap()
+    }
+
+    #[tokio::test]
+    async fn header_meta_is_added_to_tool_arguments() {
+        let meta = Map::from_iter([("traceparent".to_string(), json!("00-header-01"))]);
+
+        let arguments = call_echo(json!({ "query": "rust" }), meta.clone()).await;
+        assert_eq!(arguments["_meta"]["traceparent"], "00-header-01");
+
+        // The caller's own `_meta` wins over headers
+        let arguments = call_echo(
+            json!({ "_meta": { "traceparent": "00-argument-01" } }),
+            meta,
+        )
+        .await;
+        assert_eq!(arguments["_meta"]["traceparent"], "00-argument-01");
+    }
+
+    #[tokio::test]
+    async fn draining_servers_admit_no_requests() {
+        let shutdown = Shutdown::new();
+        let server = McpServer::new(vec![Arc::new(EchoTool)], None, &shutdown);
+
+        let admitted = server.admit().unwrap();
+        shutdown.start_draining();
+        assert!(server.admit().is_none());
+        assert_eq!(shutdown.in_flight(), 1);
+
+        // Requests admitted before draining are waited for
+        assert!(!shutdown.wait_idle(Duration::from_millis(10)).await);
+        drop(admitted);
+        assert!(shutdown.wait_idle(Duration::from_millis(10)).await);
+
+        let response = shutting_down(&json!({ "id": 7, "method": "callTool" })).unwrap();
+        assert_eq!(response["id"], 7);
+        assert_eq!(response["error"]["message"], "Server is shutting down");
+    }
+
+    #[tokio::test]
+    async fn initialize_advertises_the_baseline_capabilities() {
+        let response = server()
+            .handle(json!({ "id": 1, "method": "initialize" }), Map::new())
+            .await
+            .unwrap();
+        assert_eq!(
+            response["result"]["capabilities"],
+            json!({ "tools": {}, "prompts": {}, "resources": {} })
+        );
+        assert_eq!(response["result"]["serverInfo"]["name"], SERVER_NAME);
+
+        for (method, field) in [("listPrompts", "prompts"), ("listResources", "resources")] {
+            let response = server()
+                .handle(json!({ "id": 2, "method": method }), Map::new())
+                .await
+                .unwrap();
+            assert_eq!(response["result"][field], json!([]), "{}", method);
+        }
+
+        let response = server()
+            .handle(
+                json!({ "id": 3, "method": "getPrompt", "params": { "name": "summary" } }),
+                Map::new(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response["error"]["code"], INVALID_PARAMS);
+        assert_eq!(response["error"]["message"], "Unknown prompt: summary");
+    }
+
+    #[tokio::test]
+    async fn notifications_get_no_response() {
+        let request = json!({ "jsonrpc": "2.0", "method": "notifications/initialized" });
+        assert!(server().handle(request, Map::new()).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn unknown_methods_and_tools_are_errors() {
+        let response = server()
+            .handle(json!({ "id": 1, "method": "resources/list" }), Map::new())
+            .await
+            .unwrap();
+        assert_eq!(response["error"]["code"], METHOD_NOT_FOUND);
+
+        let response = server()
+            .handle(
+                json!({ "id": 2, "method": "callTool", "params": { "name": "crawl" } }),
+                Map::new(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response["error"]["code"], INVALID_PARAMS);
+        assert_eq!(response["id"], 2);
+    }
+}
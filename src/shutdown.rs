@@ -0,0 +1,167 @@
+use anyhow::Result;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+use tokio::sync::{Notify, watch};
+
+// Coordinates graceful shutdown: rejects new work while draining and tracks work in flight
+#[derive(Clone)]
+pub struct Shutdown {
+    inner: Arc<ShutdownState>,
+}
+
+struct ShutdownState {
+    draining: watch::Sender<bool>,
+    in_flight: AtomicUsize,
+    idle: Notify,
+}
+
+impl Shutdown {
+    pub fn new() -> Self {
+        Self {
+            inner: Arc::new(ShutdownState {
+                draining: watch::channel(false).0,
+                in_flight: AtomicUsize::new(0),
+                idle: Notify::new(),
+            }),
+        }
+    }
+
+    /// Registers a unit of work, or returns `None` once draining has started.
+    pub fn track(&self) -> Option<InFlight> {
+        self.inner.in_flight.fetch_add(1, Ordering::SeqCst);
+        let guard = InFlight {
+            state: self.inner.clone(),
+        };
+
+        if self.is_draining() {
+            return None;
+        }
+        Some(guard)
+    }
+
+    pub fn start_draining(&self) {
+        self.inner.draining.send_replace(true);
+    }
+
+    pub fn is_draining(&self) -> bool {
+        *self.inner.draining.borrow()
+    }
+
+    /// Resolves once draining has started.
+    pub async fn draining(&self) {
+        let mut draining = self.inner.draining.subscribe();
+        let _ = draining.wait_for(|draining| *draining).await;
+    }
+
+    pub fn in_flight(&self) -> usize {
+        self.inner.in_flight.load(Ordering::SeqCst)
+    }
+
+    /// Waits up to `grace` for all tracked work to finish; returns false on timeout.
+    pub async fn wait_idle(&self, grace: Duration) -> bool {
+        let idle = async {
+            while self.in_flight() > 0 {
+                self.inner.idle.notified().await;
+            }
+        };
+        tokio::time::timeout(grace, idle).await.is_ok()
+    }
+}
+
+impl Default for Shutdown {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub struct InFlight {
+    state: Arc<ShutdownState>,
+}
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        if self.state.in_flight.fetch_sub(1, Ordering::SeqCst) == 1 {
+            self.state.idle.notify_one();
+        }
+    }
+}
+
+/// Waits for SIGINT or SIGTERM and returns the signal's name.
+pub async fn signal() -> Result<&'static str> {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{SignalKind, signal};
+
+        let mut terminate = signal(SignalKind::terminate())?;
+        tokio::select! {
+            result = tokio::signal::ctrl_c() => result.map(|_| "SIGINT").map_err(Into::into),
+            _ = terminate.recv() => Ok("SIGTERM"),
+        }
+    }
+
+    #[cfg(not(unix))]
+    {
+        tokio::signal::ctrl_c().await?;
+        Ok("SIGINT")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn work_is_tracked_until_draining_starts() {
+        let shutdown = Shutdown::new();
+
+        let first = shutdown.track().unwrap();
+        let second = shutdown.track().unwrap();
+        assert_eq!(shutdown.in_flight(), 2);
+        drop(first);
+        assert_eq!(shutdown.in_flight(), 1);
+
+        shutdown.start_draining();
+        assert!(shutdown.is_draining());
+        assert!(shutdown.track().is_none());
+        assert_eq!(shutdown.in_flight(), 1);
+        drop(second);
+        assert_eq!(shutdown.in_flight(), 0);
+    }
+
+    #[tokio::test]
+    async fn draining_resolves_for_clones() {
+        let shutdown = Shutdown::new();
+        let waiter = tokio::spawn({
+            let shutdown = shutdown.clone();
+            async move { shutdown.draining().await }
+        });
+
+        shutdown.start_draining();
+        tokio::time::timeout(Duration::from_secs(1), waiter)
+            .await
+            .unwrap()
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn wait_idle_returns_when_work_finishes() {
+        let shutdown = Shutdown::new();
+        assert!(shutdown.wait_idle(Duration::ZERO).await);
+
+        let in_flight = shutdown.track().unwrap();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            drop(in_flight);
+        });
+        assert!(shutdown.wait_idle(Duration::from_secs(1)).await);
+    }
+
+    #[tokio::test]
+    async fn wait_idle_times_out_with_work_in_flight() {
+        let shutdown = Shutdown::new();
+        let _in_flight = shutdown.track().unwrap();
+
+        assert!(!shutdown.wait_idle(Duration::from_millis(20)).await);
+    }
+}
@@ -0,0 +1,213 @@
+#[cfg(feature = "otlp")]
+use anyhow::Context as _;
+use anyhow::Result;
+use opentelemetry::Context;
+#[cfg(feature = "otlp")]
+use opentelemetry::KeyValue;
+use opentelemetry::global;
+#[cfg(feature = "otlp")]
+use opentelemetry_otlp::WithExportConfig;
+use opentelemetry_sdk::propagation::TraceContextPropagator;
+#[cfg(feature = "otlp")]
+use opentelemetry_sdk::{Resource, runtime, trace};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::io::{self, Write};
+use std::sync::Arc;
+use tracing::field::Empty;
+use tracing::{Span, info_span};
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+use tracing_subscriber::fmt::MakeWriter;
+use tracing_subscriber::fmt::format::FmtSpan;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{EnvFilter, fmt};
+
+use crate::config::{LogFormat, LoggingConfig, TracingConfig};
+use crate::utils::redact::{self, Redactor};
+
+const TRACE_HEADERS: [&str; 2] = ["traceparent", "tracestate"];
+
+/// Installs the log subscriber and, when an OTLP endpoint is configured, the span exporter.
+/// `secrets` are scrubbed from every log line in addition to the configured fields.
+pub fn init(logging: &LoggingConfig, tracing: &TracingConfig, secrets: Vec<String>) -> Result<()> {
+    global::set_text_map_propagator(TraceContextPropagator::new());
+    redact::set_max_payload_bytes(logging.max_payload_bytes);
+
+    #[cfg(feature = "otlp")]
+    let otel_layer = match &tracing.otlp_endpoint {
+        Some(endpoint) => {
+            let tracer = opentelemetry_otlp::new_pipeline()
+                .tracing()
+                .with_exporter(
+                    opentelemetry_otlp::new_exporter()
+                        .tonic()
+                        .with_endpoint(endpoint),
+                )
+                .with_trace_config(trace::config().with_resource(Resource::new(vec![
+                    KeyValue::new("service.name", tracing.service_name.clone()),
+                ])))
+                .install_batch(runtime::Tokio)
+                .context("Failed to install OTLP exporter")?;
+            Some(tracing_opentelemetry::layer().with_tracer(tracer))
+        }
+        None => None,
+    };
+    #[cfg(not(feature = "otlp"))]
+    if tracing.otlp_endpoint.is_some() {
+        return Err(anyhow::anyhow!(
+            "tracing.otlp_endpoint needs OTLP export, which this build excludes (enable the `otlp` feature)"
+        ));
+    }
+
+    // Logs go to stderr so command output stays clean
+    let writer = RedactingMakeWriter {
+        redactor: Arc::new(Redactor::new(&logging.redact_fields, secrets)),
+    };
+    let (text_layer, json_layer) = match logging.format {
+        LogFormat::Text => (
+            Some(
+                fmt::layer()
+                    .with_span_events(FmtSpan::CLOSE)
+                    .with_writer(writer),
+            ),
+            None,
+        ),
+        LogFormat::Json => (
+            None,
+            Some(
+                fmt::layer()
+                    .json()
+                    .with_span_events(FmtSpan::CLOSE)
+                    .with_writer(writer),
+            ),
+        ),
+    };
+
+    let registry = tracing_subscriber::registry()
+        .with(EnvFilter::new(&logging.level))
+        .with(text_layer)
+        .with(json_layer);
+    #[cfg(feature = "otlp")]
+    registry.with(otel_layer).init();
+    #[cfg(not(feature = "otlp"))]
+    registry.init();
+
+    Ok(())
+}
+
+/// Flushes spans that have not been exported yet.
+pub fn shutdown() {
+    global::shutdown_tracer_provider();
+}
+
+/// Creates the span for a tool call, parented to the caller's trace context
+/// from the `_meta` argument if present. `_meta` is removed from `params` so it
+/// is never forwarded to Tavily.
+pub fn tool_span(tool: &str, params: &mut Value) -> Span {
+    let span = info_span!(
+        "tool.call",
+        otel.name = %format!("tool {}", tool),
+        tool,
+        query_length = Empty,
+        url_count = Empty,
+        result_count = Empty,
+        otel.status_code = Empty,
+    );
+
+    if let Some(meta) = params.as_object_mut().and_then(|p| p.remove("_meta")) {
+        let carrier: HashMap<String, String> = TRACE_HEADERS
+            .iter()
+            .filter_map(|key| {
+                meta.get(*key)
+                    .and_then(|v| v.as_str())
+                    .map(|v| (key.to_string(), v.to_string()))
+            })
+            .collect();
+        span.set_parent(extract(&carrier));
+    }
+
+    if let Some(query) = params.get("query").and_then(|q| q.as_str()) {
+        span.record("query_length", query.len());
+    }
+    if let Some(urls) = params.get("urls").and_then(|u| u.as_array()) {
+        span.record("url_count", urls.len());
+    }
+
+    span
+}
+
+/// Records the outcome of a tool call on its span.
+pub fn record_outcome(span: &Span, result_count: Option<usize>) {
+    match result_count {
+        Some(count) => {
+            span.record("result_count", count);
+        }
+        None => {
+            span.record("otel.status_code", "ERROR");
+        }
+    }
+}
+
+/// Adds the current span's trace context to outgoing NATS headers.
+#[cfg(feature = "nats")]
+pub fn inject_headers(headers: &mut async_nats::HeaderMap) {
+    let mut carrier = HashMap::new();
+    let context = Span::current().context();
+    global::get_text_map_propagator(|propagator| propagator.inject_context(&context, &mut carrier));
+
+    for (key, value) in carrier {
+        headers.insert(key.as_str(), value.as_str());
+    }
+}
+
+/// Reads the W3C trace context from NATS message headers.
+#[cfg(feature = "nats")]
+pub fn extract_headers(headers: Option<&async_nats::HeaderMap>) -> Context {
+    let carrier: HashMap<String, String> = headers
+        .map(|headers| {
+            TRACE_HEADERS
+                .iter()
+                .filter_map(|key| headers.get(*key).map(|v| (key.to_string(), v.to_string())))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    extract(&carrier)
+}
+
+fn extract(carrier: &HashMap<String, String>) -> Context {
+    global::get_text_map_propagator(|propagator| propagator.extract(carrier))
+}
+
+// Writes each formatted log event to stderr after redaction
+#[derive(Clone)]
+struct RedactingMakeWriter {
+    redactor: Arc<Redactor>,
+}
+
+impl<'a> MakeWriter<'a> for RedactingMakeWriter {
+    type Writer = RedactingWriter;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        RedactingWriter {
+            redactor: self.redactor.clone(),
+        }
+    }
+}
+
+struct RedactingWriter {
+    redactor: Arc<Redactor>,
+}
+
+impl Write for RedactingWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let text = String::from_utf8_lossy(buf);
+        io::stderr().write_all(self.redactor.redact(&text).as_bytes())?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        io::stderr().flush()
+    }
+}
@@ -0,0 +1,61 @@
+use anet_mcp_server::{Content, Tool};
+use anyhow::Result;
+use async_trait::async_trait;
+use serde_json::{Value, json};
+use std::sync::Arc;
+
+use crate::auth::{AUTHORIZATION_FIELD, Authenticator};
+use crate::tools::registry::SharedTool;
+
+// Wraps a tool so that only authenticated clients allowed by the policy can call it
+pub struct AuthorizedTool {
+    inner: SharedTool,
+    auth: Arc<Authenticator>,
+}
+
+impl AuthorizedTool {
+    pub fn new(inner: SharedTool, auth: Arc<Authenticator>) -> Self {
+        Self { inner, auth }
+    }
+}
+
+#[async_trait]
+impl Tool for AuthorizedTool {
+    fn name(&self) -> String {
+        self.inner.name()
+    }
+
+    fn description(&self) -> String {
+        self.inner.description()
+    }
+
+    fn input_schema(&self) -> Value {
+        self.inner.input_schema()
+    }
+
+    async fn call(&self, input: Option<Value>) -> Result<Vec<Content>> {
+        let mut params = input.unwrap_or_else(|| json!({}));
+
+        // The credential is removed so that it is never logged, stored with a job or sent upstream
+        let authorization = params
+            .get_mut("_meta")
+            .and_then(|meta| meta.as_object_mut())
+            .and_then(|meta| meta.remove(AUTHORIZATION_FIELD));
+        let (client_id, policy) = self
+            .auth
+            .authenticate(authorization.as_ref().and_then(|a| a.as_str()))?;
+        self.auth
+            .authorize(&client_id, policy, &self.inner.name())
+            .await?;
+
+        // The verified identity replaces whatever client ID the caller claimed
+        if let Some(params) = params.as_object_mut() {
+            let meta = params.entry("_meta").or_insert_with(|| json!({}));
+            if let Some(meta) = meta.as_object_mut() {
+                meta.insert("client_id".to_string(), json!(client_id));
+            }
+        }
+
+        self.inner.call(Some(params)).await
+    }
+}
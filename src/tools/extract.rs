@@ -1,65 +1,194 @@
 use anet_mcp_server::{Content, Tool};
-use anyhow::{Context, Result};
+use anyhow::Result;
 use async_trait::async_trait;
-use reqwest::Client;
 use serde_json::{Value, json};
-use tracing::{debug, error};
+use std::sync::Arc;
+use std::time::Instant;
+use tracing::{Instrument, debug, error, warn};
 
+use crate::audit::{AuditLog, AuditRecord, Caller};
+use crate::auth::Authenticator;
+use crate::cache::Cache;
+use crate::client::{TavilyClient, is_unavailable};
+use crate::keys::ApiKey;
+use crate::metrics::metrics;
 use crate::models::tavily::TavilyExtractResponse;
-use crate::utils::formatter::format_tavily_extract_results;
+use crate::telemetry;
+use crate::usage::UsageAccountant;
+use crate::utils::credits::estimate_credits;
+use crate::utils::formatter::{format_stale_notice, format_tavily_extract_results};
+use crate::utils::params::ParamOverrides;
+use crate::utils::redact::truncate_payload;
+use crate::utils::response_format::ResponseFormat;
+use crate::utils::schema;
+use crate::utils::time::format_utc;
 
 // Tavily Extract Tool
 pub struct TavilyExtractTool {
-    api_key: String,
-    client: Client,
+    client: Arc<TavilyClient>,
+    cache: Cache,
+    overrides: ParamOverrides,
+    audit: AuditLog,
+    usage: Option<Arc<UsageAccountant>>,
+    auth: Option<Arc<Authenticator>>,
 }
 
 impl TavilyExtractTool {
-    pub fn new(api_key: String) -> Result<Self> {
-        debug!(
-            "Creating TavilyExtractTool with API key: {}",
-            api_key.chars().take(5).collect::<String>() + "..."
-        );
+    pub fn new(client: Arc<TavilyClient>) -> Self {
+        debug!("Creating TavilyExtractTool");
+
+        Self {
+            client,
+            cache: Cache::disabled(),
+            overrides: ParamOverrides::default(),
+            audit: AuditLog::disabled(),
+            usage: None,
+            auth: None,
+        }
+    }
+
+    pub fn with_cache(mut self, cache: Cache) -> Self {
+        self.cache = cache;
+        self
+    }
+
+    pub fn with_overrides(mut self, overrides: ParamOverrides) -> Self {
+        self.overrides = overrides;
+        self
+    }
+
+    pub fn with_audit(mut self, audit: AuditLog) -> Self {
+        self.audit = audit;
+        self
+    }
 
-        let client = Client::new();
+    pub fn with_usage(mut self, usage: Arc<UsageAccountant>) -> Self {
+        self.usage = Some(usage);
+        self
+    }
 
-        Ok(Self { api_key, client })
+    /// Checks the arguments sent to Tavily, after defaults and forced values are
+    /// applied, against the caller's policy.
+    pub fn with_auth(mut self, auth: Arc<Authenticator>) -> Self {
+        self.auth = Some(auth);
+        self
     }
 
-    async fn extract(&self, params: Value) -> Result<TavilyExtractResponse> {
-        let extract_params = params.clone();
+    // Applies the configured defaults and forced parameters
+    fn prepare(&self, params: &Value) -> Value {
+        let mut extract_params = params.clone();
+        self.overrides.apply_defaults(&mut extract_params);
+        self.overrides.apply_forced(&mut extract_params);
+        extract_params
+    }
 
+    async fn extract(
+        &self,
+        extract_params: &Value,
+        caller: &Caller,
+        client_key: Option<&ApiKey>,
+    ) -> Result<(TavilyExtractResponse, Option<u64>)> {
         debug!(
             "Extract parameters: {}",
-            serde_json::to_string_pretty(&extract_params)?
+            truncate_payload(&serde_json::to_string_pretty(extract_params)?)
         );
 
-        let response = self
-            .client
-            .post("https://api.tavily.com/extract")
-            .header("Authorization", format!("Bearer {}", self.api_key))
-            .json(&extract_params)
-            .send()
-            .await?;
-
-        debug!("Tavily API response status: {}", response.status());
-
-        if !response.status().is_success() {
-            let status = response.status();
-            let error_text = response.text().await?;
-            error!("Tavily API error: {} - {}", status, error_text);
-            return Err(anyhow::anyhow!("Tavily API error: {}", error_text));
+        let cache_key = Cache::key("extract", extract_params);
+        if let Some(cached) = self.cache.get::<TavilyExtractResponse>(&cache_key).await {
+            return Ok((cached, None));
         }
 
-        // For debugging the exact response format
-        let response_text = response.text().await?;
-        debug!("Raw extract API response: {}", response_text);
+        // Cached responses are free and callers' own keys are billed to them, so the
+        // budget only applies to upstream calls made with the server's keys
+        let usage = self.usage.as_ref().filter(|_| client_key.is_none());
+        let credits = estimate_credits("extract", extract_params);
+        let reservation = match usage {
+            Some(usage) => Some(usage.reserve(caller, credits).await?),
+            None => None,
+        };
 
-        let extract_response = serde_json::from_str::<TavilyExtractResponse>(&response_text)
-            .context("Failed to parse Tavily extract response")?;
+        let extract_response = match self
+            .client
+            .post_with_key::<TavilyExtractResponse>("extract", extract_params, client_key)
+            .await
+        {
+            Ok(response) => response,
+            Err(e) => {
+                if let Some(reservation) = reservation {
+                    reservation.refund().await;
+                }
+
+                // An expired answer is better than none while Tavily is down, but a
+                // rejected request or key has to reach the caller
+                if !is_unavailable(&e) {
+                    return Err(e);
+                }
+                let Some(stale) = self
+                    .cache
+                    .get_stale::<TavilyExtractResponse>(&cache_key)
+                    .await
+                else {
+                    return Err(e);
+                };
+                warn!(
+                    "Tavily extract failed ({}), serving cached response fetched at {}",
+                    e,
+                    format_utc(stale.fetched_at)
+                );
+                // Refreshes run with the server's keys, so callers with their own don't start one
+                if client_key.is_none() {
+                    self.refresh_in_background(
+                        &cache_key,
+                        extract_params,
+                        caller,
+                        stale.fetched_at,
+                    );
+                }
+                return Ok((stale.value, Some(stale.fetched_at)));
+            }
+        };
 
         debug!("Successfully parsed Tavily API extract response");
-        Ok(extract_response)
+
+        self.cache.put(&cache_key, &extract_response).await;
+        Ok((extract_response, None))
+    }
+
+    // Retries the request until Tavily recovers and replaces the stale entry
+    fn refresh_in_background(
+        &self,
+        cache_key: &str,
+        params: &Value,
+        caller: &Caller,
+        fetched_at: u64,
+    ) {
+        let client = self.client.clone();
+        let usage = self.usage.clone();
+        let params = params.clone();
+        let caller = caller.clone();
+
+        self.cache.revalidate(cache_key, fetched_at, move || {
+            let client = client.clone();
+            let usage = usage.clone();
+            let params = params.clone();
+            let caller = caller.clone();
+            async move {
+                let credits = estimate_credits("extract", &params);
+                let reservation = match &usage {
+                    Some(usage) => Some(usage.reserve(&caller, credits).await?),
+                    None => None,
+                };
+                let response = client
+                    .post::<TavilyExtractResponse>("extract", &params)
+                    .await;
+                if response.is_err()
+                    && let Some(reservation) = reservation
+                {
+                    reservation.refund().await;
+                }
+                response
+            }
+        });
     }
 }
 
@@ -80,6 +209,7 @@ impl Tool for TavilyExtractTool {
                 "urls": {
                     "type": "array",
                     "items": { "type": "string" },
+                    "minItems": 1,
                     "description": "List of URLs to extract content from"
                 },
                 "extract_depth": {
@@ -99,12 +229,58 @@ impl Tool for TavilyExtractTool {
     }
 
     async fn call(&self, input: Option<Value>) -> Result<Vec<Content>> {
-        let params = input.unwrap_or_else(|| json!({}));
+        let mut params = input.unwrap_or_else(|| json!({}));
+        let caller = Caller::from_params(&params);
+        let format = ResponseFormat::from_params(&params);
+        let client_key = self.client.take_client_key(&mut params);
+        let span = telemetry::tool_span(&self.name(), &mut params);
 
-        match self.extract(params).await {
-            Ok(response) => {
+        let _in_flight = metrics().start_tool_call();
+        let started = Instant::now();
+        let extract_params = self.prepare(&params);
+        let audit = AuditRecord::new("tavily-extract", caller.clone(), extract_params.clone());
+        // Bad arguments are rejected here rather than spending a request on them
+        let allowed = schema::validate(&self.input_schema(), &extract_params)
+            .map_err(|e| anyhow::anyhow!("Invalid tavily-extract arguments: {}", e))
+            .and_then(|()| match &self.auth {
+                Some(auth) => auth.check_parameters(caller.client_id.as_deref(), &extract_params),
+                None => Ok(()),
+            });
+        let result = match allowed.and(client_key) {
+            Ok(client_key) => {
+                self.extract(&extract_params, &caller, client_key.as_ref())
+                    .instrument(span.clone())
+                    .await
+            }
+            Err(e) => Err(e),
+        };
+        telemetry::record_outcome(&span, result.as_ref().ok().map(|(r, _)| r.results.len()));
+        metrics().record_tool_call("tavily-extract", result.is_ok(), started.elapsed());
+        self.audit
+            .record(match &result {
+                Ok((response, _)) => audit.completed(
+                    response.results.len(),
+                    response.request_id.clone(),
+                    started.elapsed(),
+                ),
+                Err(e) => audit.failed(e, started.elapsed()),
+            })
+            .await;
+
+        match result {
+            Ok((mut response, stale)) if format == ResponseFormat::Json => {
+                response.stale = stale.is_some();
+                response.fetched_at = stale;
+                Ok(vec![Content::Text {
+                    text: serde_json::to_string(&response)?,
+                }])
+            }
+            Ok((response, stale)) => {
                 // Format the response with the extract-specific formatter
-                let formatted = format_tavily_extract_results(&response);
+                let mut formatted = format_tavily_extract_results(&response);
+                if let Some(fetched_at) = stale {
+                    formatted = format!("{}\n{}", format_stale_notice(fetched_at), formatted);
+                }
                 Ok(vec![Content::Text { text: formatted }])
             }
             Err(e) => {
@@ -0,0 +1,147 @@
+use anet_mcp_server::{Content, Tool};
+use anyhow::Result;
+use async_trait::async_trait;
+use serde_json::{Value, json};
+use tracing::{debug, error};
+
+use crate::audit::Caller;
+use crate::jobs::JobQueue;
+use crate::keys::CLIENT_KEY_FIELD;
+use crate::tools::registry::SharedTool;
+
+// Wraps a tool so callers can pass `"async": true` and get a job ID back immediately
+pub struct AsyncJobTool {
+    inner: SharedTool,
+    queue: JobQueue,
+}
+
+impl AsyncJobTool {
+    pub fn new(inner: SharedTool, queue: JobQueue) -> Self {
+        Self { inner, queue }
+    }
+}
+
+#[async_trait]
+impl Tool for AsyncJobTool {
+    fn name(&self) -> String {
+        self.inner.name()
+    }
+
+    fn description(&self) -> String {
+        format!(
+            "{} Set 'async' to true to run it as a background job and poll the result with tavily-job-status.",
+            self.inner.description()
+        )
+    }
+
+    fn input_schema(&self) -> Value {
+        let mut schema = self.inner.input_schema();
+        if let Some(properties) = schema.get_mut("properties").and_then(|p| p.as_object_mut()) {
+            properties.insert(
+                "async".to_string(),
+                json!({
+                    "type": "boolean",
+                    "description": "Run as a background job and return a job ID immediately instead of waiting for the result",
+                    "default": false
+                }),
+            );
+        }
+        schema
+    }
+
+    async fn call(&self, input: Option<Value>) -> Result<Vec<Content>> {
+        let mut params = input.unwrap_or_else(|| json!({}));
+
+        let run_async = params
+            .as_object_mut()
+            .and_then(|p| p.remove("async"))
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+
+        if !run_async {
+            return self.inner.call(Some(params)).await;
+        }
+
+        // Job arguments are persisted in JetStream, which is no place for a secret
+        let has_client_key = params
+            .get("_meta")
+            .and_then(|meta| meta.get(CLIENT_KEY_FIELD))
+            .is_some();
+        if has_client_key {
+            return Err(anyhow::anyhow!(
+                "Per-request API keys cannot be used with async jobs"
+            ));
+        }
+
+        let owner = Caller::from_params(&params).label();
+        let record = self
+            .queue
+            .submit(&self.inner.name(), params, &owner)
+            .await?;
+        debug!("Submitted {} as job {}", record.tool, record.id);
+
+        let text = serde_json::to_string_pretty(&json!({
+            "job_id": record.id,
+            "status": record.status,
+            "completion_subject": self.queue.completion_subject(&record.id),
+        }))?;
+        Ok(vec![Content::Text { text }])
+    }
+}
+
+// Tavily Job Status Tool
+pub struct TavilyJobStatusTool {
+    queue: JobQueue,
+}
+
+impl TavilyJobStatusTool {
+    pub fn new(queue: JobQueue) -> Self {
+        Self { queue }
+    }
+}
+
+#[async_trait]
+impl Tool for TavilyJobStatusTool {
+    fn name(&self) -> String {
+        "tavily-job-status".to_string()
+    }
+
+    fn description(&self) -> String {
+        "Returns the status of a background job started with 'async': true by the same client, including its result once completed.".to_string()
+    }
+
+    fn input_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "job_id": {
+                    "type": "string",
+                    "description": "The job ID returned when the job was submitted"
+                }
+            },
+            "required": ["job_id"]
+        })
+    }
+
+    async fn call(&self, input: Option<Value>) -> Result<Vec<Content>> {
+        let params = input.unwrap_or_else(|| json!({}));
+        let job_id = params
+            .get("job_id")
+            .and_then(|id| id.as_str())
+            .ok_or_else(|| anyhow::anyhow!("Missing required parameter: job_id"))?;
+
+        // Other clients' jobs are reported as unknown rather than forbidden
+        let caller = Caller::from_params(&params).label();
+        match self.queue.get(job_id).await {
+            Ok(Some(record)) if record.owner.as_deref() == Some(caller.as_str()) => {
+                let text = serde_json::to_string_pretty(&record)?;
+                Ok(vec![Content::Text { text }])
+            }
+            Ok(_) => Err(anyhow::anyhow!("Unknown or expired job: {}", job_id)),
+            Err(e) => {
+                error!("Job status error: {}", e);
+                Err(e)
+            }
+        }
+    }
+}
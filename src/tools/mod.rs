@@ -1,2 +1,7 @@
+pub mod auth;
 pub mod extract;
+#[cfg(feature = "nats")]
+pub mod job;
+pub mod registry;
 pub mod search;
+pub mod usage;
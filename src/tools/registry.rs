@@ -0,0 +1,76 @@
+use anet_mcp_server::{Content, Tool};
+use std::sync::Arc;
+
+use crate::audit::AuditLog;
+use crate::auth::Authenticator;
+use crate::cache::Cache;
+use crate::client::TavilyClient;
+use crate::config::ToolsConfig;
+use crate::tools::extract::TavilyExtractTool;
+use crate::tools::search::TavilySearchTool;
+use crate::tools::usage::TavilyUsageTool;
+use crate::usage::{AccountUsage, UsageAccountant};
+use crate::utils::params::ParamOverrides;
+
+/// A tool that can be shared between the server, the job worker and the CLI.
+pub type SharedTool = Arc<dyn Tool + Send + Sync>;
+
+/// Builds every tool enabled in the configuration. With `auth`, the arguments
+/// each call sends to Tavily are checked against the caller's policy.
+pub fn build_tools(
+    config: &ToolsConfig,
+    client: Arc<TavilyClient>,
+    cache: Cache,
+    audit: AuditLog,
+    usage: Arc<UsageAccountant>,
+    auth: Option<Arc<Authenticator>>,
+) -> Vec<SharedTool> {
+    let mut tools: Vec<SharedTool> = Vec::new();
+
+    if config.search.enabled {
+        let mut search = TavilySearchTool::new(client.clone())
+            .with_cache(cache.clone())
+            .with_overrides(ParamOverrides::from_config(&config.search))
+            .with_audit(audit.clone())
+            .with_usage(usage.clone());
+        if let Some(auth) = &auth {
+            search = search.with_auth(auth.clone());
+        }
+        tools.push(Arc::new(search));
+    }
+
+    if config.extract.enabled {
+        let mut extract = TavilyExtractTool::new(client.clone())
+            .with_cache(cache)
+            .with_overrides(ParamOverrides::from_config(&config.extract))
+            .with_audit(audit)
+            .with_usage(usage.clone());
+        if let Some(auth) = auth {
+            extract = extract.with_auth(auth);
+        }
+        tools.push(Arc::new(extract));
+    }
+
+    tools.push(Arc::new(TavilyUsageTool::new(
+        usage,
+        AccountUsage::new(client, &config.usage),
+    )));
+    tools
+}
+
+pub fn find_tool<'a>(tools: &'a [SharedTool], name: &str) -> Option<&'a SharedTool> {
+    tools.iter().find(|tool| tool.name() == name)
+}
+
+/// Joins the text parts of a tool result.
+pub fn content_to_text(content: &[Content]) -> String {
+    content
+        .iter()
+        .filter_map(|c| match c {
+            Content::Text { text } => Some(text.as_str()),
+            #[allow(unreachable_patterns)]
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
@@ -1,67 +1,191 @@
 use anet_mcp_server::{Content, Tool};
 use anyhow::Result;
 use async_trait::async_trait;
-use reqwest::Client;
 use serde_json::{Value, json};
-use tracing::{debug, error};
+use std::sync::Arc;
+use std::time::Instant;
+use tracing::{Instrument, debug, error, warn};
 
+use crate::audit::{AuditLog, AuditRecord, Caller};
+use crate::auth::Authenticator;
+use crate::cache::Cache;
+use crate::client::{TavilyClient, is_unavailable};
+use crate::keys::ApiKey;
+use crate::metrics::metrics;
 use crate::models::tavily::TavilyResponse;
-use crate::utils::formatter::format_tavily_results;
+use crate::telemetry;
+use crate::usage::UsageAccountant;
+use crate::utils::credits::estimate_credits;
+use crate::utils::formatter::{format_stale_notice, format_tavily_results};
+use crate::utils::params::ParamOverrides;
+use crate::utils::redact::truncate_payload;
+use crate::utils::response_format::ResponseFormat;
+use crate::utils::schema;
+use crate::utils::time::format_utc;
 
 // Tavily Search Tool
 pub struct TavilySearchTool {
-    api_key: String,
-    client: Client,
+    client: Arc<TavilyClient>,
+    cache: Cache,
+    overrides: ParamOverrides,
+    audit: AuditLog,
+    usage: Option<Arc<UsageAccountant>>,
+    auth: Option<Arc<Authenticator>>,
 }
 
 impl TavilySearchTool {
-    pub fn new(api_key: String) -> Result<Self> {
-        debug!(
-            "Creating TavilySearchTool with API key: {}",
-            api_key.chars().take(5).collect::<String>() + "..."
-        );
+    pub fn new(client: Arc<TavilyClient>) -> Self {
+        debug!("Creating TavilySearchTool");
 
-        let client = Client::new();
+        Self {
+            client,
+            cache: Cache::disabled(),
+            overrides: ParamOverrides::default(),
+            audit: AuditLog::disabled(),
+            usage: None,
+            auth: None,
+        }
+    }
 
-        Ok(Self { api_key, client })
+    pub fn with_cache(mut self, cache: Cache) -> Self {
+        self.cache = cache;
+        self
     }
 
-    async fn search(&self, params: Value) -> Result<TavilyResponse> {
+    pub fn with_overrides(mut self, overrides: ParamOverrides) -> Self {
+        self.overrides = overrides;
+        self
+    }
+
+    pub fn with_audit(mut self, audit: AuditLog) -> Self {
+        self.audit = audit;
+        self
+    }
+
+    pub fn with_usage(mut self, usage: Arc<UsageAccountant>) -> Self {
+        self.usage = Some(usage);
+        self
+    }
+
+    /// Checks the arguments sent to Tavily, after defaults and forced values are
+    /// applied, against the caller's policy.
+    pub fn with_auth(mut self, auth: Arc<Authenticator>) -> Self {
+        self.auth = Some(auth);
+        self
+    }
+
+    // Applies the configured defaults, the news heuristic and forced parameters
+    fn prepare(&self, params: &Value) -> Value {
         let mut search_params = params.clone();
+        self.overrides.apply_defaults(&mut search_params);
 
         // Add news topic if query contains "news"
-        if let Some(query) = params.get("query").and_then(|q| q.as_str()) {
-            if query.to_lowercase().contains("news") && !search_params.get("topic").is_some() {
-                search_params["topic"] = json!("news");
-            }
+        if let Some(query) = params.get("query").and_then(|q| q.as_str())
+            && query.to_lowercase().contains("news")
+            && search_params.get("topic").is_none()
+        {
+            search_params["topic"] = json!("news");
         }
 
+        self.overrides.apply_forced(&mut search_params);
+        search_params
+    }
+
+    async fn search(
+        &self,
+        search_params: &Value,
+        caller: &Caller,
+        client_key: Option<&ApiKey>,
+    ) -> Result<(TavilyResponse, Option<u64>)> {
         debug!(
             "Search parameters: {}",
-            serde_json::to_string_pretty(&search_params)?
+            truncate_payload(&serde_json::to_string_pretty(search_params)?)
         );
-        debug!("Sending request to Tavily API with API key in Authorization header");
 
-        let response = self
-            .client
-            .post("https://api.tavily.com/search")
-            .header("Authorization", format!("Bearer {}", self.api_key))
-            .json(&search_params)
-            .send()
-            .await?;
-
-        debug!("Tavily API response status: {}", response.status());
-
-        if !response.status().is_success() {
-            let status = response.status();
-            let error_text = response.text().await?;
-            error!("Tavily API error: {} - {}", status, error_text);
-            return Err(anyhow::anyhow!("Tavily API error: {}", error_text));
+        let cache_key = Cache::key("search", search_params);
+        if let Some(cached) = self.cache.get::<TavilyResponse>(&cache_key).await {
+            return Ok((cached, None));
         }
 
-        let tavily_response = response.json::<TavilyResponse>().await?;
+        // Cached responses are free and callers' own keys are billed to them, so the
+        // budget only applies to upstream calls made with the server's keys
+        let usage = self.usage.as_ref().filter(|_| client_key.is_none());
+        let credits = estimate_credits("search", search_params);
+        let reservation = match usage {
+            Some(usage) => Some(usage.reserve(caller, credits).await?),
+            None => None,
+        };
+
+        let tavily_response = match self
+            .client
+            .post_with_key::<TavilyResponse>("search", search_params, client_key)
+            .await
+        {
+            Ok(response) => response,
+            Err(e) => {
+                if let Some(reservation) = reservation {
+                    reservation.refund().await;
+                }
+
+                // An expired answer is better than none while Tavily is down, but a
+                // rejected request or key has to reach the caller
+                if !is_unavailable(&e) {
+                    return Err(e);
+                }
+                let Some(stale) = self.cache.get_stale::<TavilyResponse>(&cache_key).await else {
+                    return Err(e);
+                };
+                warn!(
+                    "Tavily search failed ({}), serving cached response fetched at {}",
+                    e,
+                    format_utc(stale.fetched_at)
+                );
+                // Refreshes run with the server's keys, so callers with their own don't start one
+                if client_key.is_none() {
+                    self.refresh_in_background(&cache_key, search_params, caller, stale.fetched_at);
+                }
+                return Ok((stale.value, Some(stale.fetched_at)));
+            }
+        };
         debug!("Successfully parsed Tavily API response");
-        Ok(tavily_response)
+
+        self.cache.put(&cache_key, &tavily_response).await;
+        Ok((tavily_response, None))
+    }
+
+    // Retries the request until Tavily recovers and replaces the stale entry
+    fn refresh_in_background(
+        &self,
+        cache_key: &str,
+        params: &Value,
+        caller: &Caller,
+        fetched_at: u64,
+    ) {
+        let client = self.client.clone();
+        let usage = self.usage.clone();
+        let params = params.clone();
+        let caller = caller.clone();
+
+        self.cache.revalidate(cache_key, fetched_at, move || {
+            let client = client.clone();
+            let usage = usage.clone();
+            let params = params.clone();
+            let caller = caller.clone();
+            async move {
+                let credits = estimate_credits("search", &params);
+                let reservation = match &usage {
+                    Some(usage) => Some(usage.reserve(&caller, credits).await?),
+                    None => None,
+                };
+                let response = client.post::<TavilyResponse>("search", &params).await;
+                if response.is_err()
+                    && let Some(reservation) = reservation
+                {
+                    reservation.refund().await;
+                }
+                response
+            }
+        });
     }
 }
 
@@ -81,6 +205,7 @@ impl Tool for TavilySearchTool {
             "properties": {
                 "query": {
                     "type": "string",
+                    "minLength": 1,
                     "description": "Search query"
                 },
                 "search_depth": {
@@ -145,16 +270,62 @@ impl Tool for TavilySearchTool {
     }
 
     async fn call(&self, input: Option<Value>) -> Result<Vec<Content>> {
-        let params = input.unwrap_or_else(|| json!({}));
+        let mut params = input.unwrap_or_else(|| json!({}));
+        let caller = Caller::from_params(&params);
+        let format = ResponseFormat::from_params(&params);
+        let client_key = self.client.take_client_key(&mut params);
+        let span = telemetry::tool_span(&self.name(), &mut params);
 
         debug!(
             "TavilySearchTool call with params: {}",
-            serde_json::to_string_pretty(&params)?
+            truncate_payload(&serde_json::to_string_pretty(&params)?)
         );
 
-        match self.search(params).await {
-            Ok(response) => {
-                let formatted = format_tavily_results(&response);
+        let _in_flight = metrics().start_tool_call();
+        let started = Instant::now();
+        let search_params = self.prepare(&params);
+        let audit = AuditRecord::new("tavily-search", caller.clone(), search_params.clone());
+        // Bad arguments are rejected here rather than spending a request on them
+        let allowed = schema::validate(&self.input_schema(), &search_params)
+            .map_err(|e| anyhow::anyhow!("Invalid tavily-search arguments: {}", e))
+            .and_then(|()| match &self.auth {
+                Some(auth) => auth.check_parameters(caller.client_id.as_deref(), &search_params),
+                None => Ok(()),
+            });
+        let result = match allowed.and(client_key) {
+            Ok(client_key) => {
+                self.search(&search_params, &caller, client_key.as_ref())
+                    .instrument(span.clone())
+                    .await
+            }
+            Err(e) => Err(e),
+        };
+        telemetry::record_outcome(&span, result.as_ref().ok().map(|(r, _)| r.results.len()));
+        metrics().record_tool_call("tavily-search", result.is_ok(), started.elapsed());
+        self.audit
+            .record(match &result {
+                Ok((response, _)) => audit.completed(
+                    response.results.len(),
+                    response.request_id.clone(),
+                    started.elapsed(),
+                ),
+                Err(e) => audit.failed(e, started.elapsed()),
+            })
+            .await;
+
+        match result {
+            Ok((mut response, stale)) if format == ResponseFormat::Json => {
+                response.stale = stale.is_some();
+                response.fetched_at = stale;
+                Ok(vec![Content::Text {
+                    text: serde_json::to_string(&response)?,
+                }])
+            }
+            Ok((response, stale)) => {
+                let mut formatted = format_tavily_results(&response);
+                if let Some(fetched_at) = stale {
+                    formatted = format!("{}\n{}", format_stale_notice(fetched_at), formatted);
+                }
                 debug!("Successfully formatted Tavily search results");
                 Ok(vec![Content::Text { text: formatted }])
             }
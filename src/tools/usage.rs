@@ -0,0 +1,63 @@
+use anet_mcp_server::{Content, Tool};
+use anyhow::Result;
+use async_trait::async_trait;
+use serde_json::{Value, json};
+use std::sync::Arc;
+
+use crate::audit::Caller;
+use crate::usage::{AccountUsage, UsageAccountant};
+
+// Tavily Usage Tool
+pub struct TavilyUsageTool {
+    usage: Arc<UsageAccountant>,
+    account: AccountUsage,
+}
+
+impl TavilyUsageTool {
+    pub fn new(usage: Arc<UsageAccountant>, account: AccountUsage) -> Self {
+        Self { usage, account }
+    }
+}
+
+#[async_trait]
+impl Tool for TavilyUsageTool {
+    fn name(&self) -> String {
+        "tavily-usage".to_string()
+    }
+
+    fn description(&self) -> String {
+        "Reports the Tavily API key and plan usage and limits of every configured key, plus the credits spent through this server today and this month, in total and by the calling client, along with the configured budgets.".to_string()
+    }
+
+    fn input_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "refresh": {
+                    "type": "boolean",
+                    "description": "Query Tavily even if a recent usage answer is cached",
+                    "default": false
+                }
+            }
+        })
+    }
+
+    async fn call(&self, input: Option<Value>) -> Result<Vec<Content>> {
+        let params = input.unwrap_or_else(|| json!({}));
+        let caller = Caller::from_params(&params);
+        let refresh = params
+            .get("refresh")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+
+        // Keys Tavily cannot answer for carry an error, and the local totals are still reported
+        let account = self.account.fetch(refresh).await;
+
+        let text = serde_json::to_string_pretty(&json!({
+            "account": account,
+            "keys": self.account.keys(),
+            "server": self.usage.report(&caller).await?,
+        }))?;
+        Ok(vec![Content::Text { text }])
+    }
+}
@@ -0,0 +1,47 @@
+#[cfg(feature = "nats")]
+pub mod nats;
+
+use serde_json::{Map, Value};
+
+use crate::auth::AUTHORIZATION_FIELD;
+use crate::keys::CLIENT_KEY_FIELD;
+
+/// Request headers copied into the `_meta` argument of tool calls, paired with
+/// the `_meta` field each one fills.
+const META_HEADERS: [(&str, &str); 4] = [
+    ("Authorization", AUTHORIZATION_FIELD),
+    ("traceparent", "traceparent"),
+    ("tracestate", "tracestate"),
+    ("Tavily-Api-Key", CLIENT_KEY_FIELD),
+];
+
+/// Collects the `_meta` fields carried by request headers. `header` returns the
+/// value of the named header, if the request has it.
+pub fn meta_from_headers(header: impl Fn(&str) -> Option<String>) -> Map<String, Value> {
+    META_HEADERS
+        .iter()
+        .filter_map(|(name, field)| {
+            header(name).map(|value| (field.to_string(), Value::String(value)))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_known_headers_become_meta() {
+        let meta = meta_from_headers(|name| match name {
+            "traceparent" => Some("00-abc-01".to_string()),
+            "Authorization" => Some("Bearer token".to_string()),
+            "Tavily-Api-Key" => Some("tvly-client".to_string()),
+            "x-other" => Some("ignored".to_string()),
+            _ => None,
+        });
+        assert_eq!(meta.len(), 3);
+        assert_eq!(meta[AUTHORIZATION_FIELD], "Bearer token");
+        assert_eq!(meta["traceparent"], "00-abc-01");
+        assert_eq!(meta[CLIENT_KEY_FIELD], "tvly-client");
+    }
+}
@@ -0,0 +1,129 @@
+use anyhow::{Context, Result};
+use futures_util::StreamExt;
+use serde_json::{Map, Value};
+use std::time::Duration;
+use tokio::sync::Notify;
+use tracing::{debug, info, warn};
+
+use crate::server::{self, McpServer};
+use crate::shutdown::InFlight;
+use crate::transport::meta_from_headers;
+
+// Serves MCP requests published to a NATS subject. Each request is answered on
+// its reply subject, and its headers are passed to the server as `_meta` fields
+pub struct NatsTransport {
+    client: async_nats::Client,
+    subject: String,
+    stop: Notify,
+}
+
+// How long the drain waits for another buffered request before it is done
+const DRAIN_IDLE: Duration = Duration::from_millis(500);
+
+impl NatsTransport {
+    pub async fn connect(nats_url: &str, subject: &str) -> Result<Self> {
+        let client = async_nats::connect(nats_url)
+            .await
+            .with_context(|| format!("Failed to connect to NATS at {}", nats_url))?;
+        Ok(Self {
+            client,
+            subject: subject.to_string(),
+            stop: Notify::new(),
+        })
+    }
+
+    /// The transport's connection, shared with the health responder and job queue.
+    pub fn client(&self) -> &async_nats::Client {
+        &self.client
+    }
+
+    /// Answers requests until the subscription ends or [`NatsTransport::stop`] is
+    /// called. Each request runs in its own task so slow tool calls don't hold up
+    /// the others, and is admitted as in flight as soon as it arrives.
+    pub async fn serve(&self, server: McpServer) -> Result<()> {
+        let mut requests = self
+            .client
+            .subscribe(self.subject.clone())
+            .await
+            .with_context(|| format!("Failed to subscribe to {}", self.subject))?;
+
+        loop {
+            tokio::select! {
+                message = requests.next() => match message {
+                    Some(message) => self.dispatch(&server, message),
+                    None => return Ok(()),
+                },
+                _ = self.stop.notified() => break,
+            }
+        }
+
+        // Stop new requests from arriving, then answer the ones already received
+        info!("Unsubscribing from {}", self.subject);
+        requests
+            .unsubscribe()
+            .await
+            .with_context(|| format!("Failed to unsubscribe from {}", self.subject))?;
+        let mut drained = 0;
+        while let Ok(Some(message)) = tokio::time::timeout(DRAIN_IDLE, requests.next()).await {
+            self.dispatch(&server, message);
+            drained += 1;
+        }
+        debug!(
+            "Dispatched {} buffered request(s) after unsubscribing",
+            drained
+        );
+        Ok(())
+    }
+
+    /// Makes [`NatsTransport::serve`] unsubscribe and return once the requests
+    /// it has already received are dispatched.
+    pub fn stop(&self) {
+        self.stop.notify_one();
+    }
+
+    fn dispatch(&self, server: &McpServer, message: async_nats::Message) {
+        let in_flight = server.admit();
+        tokio::spawn(respond(
+            self.client.clone(),
+            server.clone(),
+            message,
+            in_flight,
+        ));
+    }
+}
+
+async fn respond(
+    client: async_nats::Client,
+    server: McpServer,
+    message: async_nats::Message,
+    in_flight: Option<InFlight>,
+) {
+    let meta = match &message.headers {
+        Some(headers) => meta_from_headers(|name| headers.get(name).map(|v| v.to_string())),
+        None => Map::new(),
+    };
+    let response = match serde_json::from_slice::<Value>(&message.payload) {
+        Ok(request) if in_flight.is_some() => server.handle(request, meta).await,
+        Ok(request) => server::shutting_down(&request),
+        Err(e) => Some(server::parse_error(&e)),
+    };
+
+    let Some(response) = response else {
+        return;
+    };
+    let Some(reply) = message.reply else {
+        debug!("Dropping response to a request without a reply subject");
+        return;
+    };
+    let payload = match serde_json::to_vec(&response) {
+        Ok(payload) => payload,
+        Err(e) => {
+            warn!("Failed to serialize MCP response: {}", e);
+            return;
+        }
+    };
+    if let Err(e) = client.publish(reply, payload.into()).await {
+        warn!("Failed to publish MCP response: {}", e);
+    }
+    drop(in_flight);
+}
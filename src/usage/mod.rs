@@ -0,0 +1,555 @@
+#[cfg(feature = "nats")]
+pub mod nats_kv;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use futures_util::future::join_all;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::{debug, warn};
+
+use crate::audit::Caller;
+use crate::auth::policy::Policy;
+use crate::client::TavilyClient;
+#[cfg(feature = "nats")]
+use crate::config::UsageStoreKind;
+use crate::config::{BudgetConfig, UsageToolConfig};
+use crate::keys::KeyStatus;
+use crate::models::tavily::TavilyUsageResponse;
+#[cfg(feature = "nats")]
+use crate::usage::nats_kv::NatsKvUsageStore;
+use crate::utils::time::{civil_date, unix_millis};
+
+/// A change applied to the counters, failing to leave them untouched.
+pub type UsageChange<'a> = dyn Fn(&mut UsageState) -> Result<()> + Send + Sync + 'a;
+
+/// Where the credit counters are kept.
+#[async_trait]
+pub trait UsageStore: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    /// Returns the counters as last stored.
+    async fn load(&self) -> Result<UsageState>;
+
+    /// Applies `change` to the current counters and stores the result, or
+    /// stores nothing if `change` fails. A store shared between replicas runs
+    /// `change` again on fresh counters when another replica wrote first.
+    async fn update(&self, change: &UsageChange<'_>) -> Result<UsageState>;
+}
+
+// Tracks estimated credit spend per UTC day, month and client, and enforces the configured budgets
+pub struct UsageAccountant {
+    budget: BudgetConfig,
+    policy: Option<Arc<Policy>>,
+    store: Arc<dyn UsageStore>,
+}
+
+/// Credits spent in the current UTC day and month, in total and by client.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UsageState {
+    day: String,
+    month: String,
+    day_total: u64,
+    month_total: u64,
+    day_by_client: HashMap<String, u64>,
+    month_by_client: HashMap<String, u64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UsageReport {
+    pub day: String,
+    pub month: String,
+    pub day_credits: u64,
+    pub month_credits: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub daily_budget: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub monthly_budget: Option<u64>,
+    pub day_credits_by_client: BTreeMap<String, u64>,
+    pub month_credits_by_client: BTreeMap<String, u64>,
+}
+
+impl UsageAccountant {
+    /// Keeps the counters in this process, for one-off commands and tests.
+    pub fn new(budget: BudgetConfig) -> Self {
+        Self {
+            budget,
+            policy: None,
+            store: Arc::new(MemoryUsageStore::default()),
+        }
+    }
+
+    /// Keeps the counters in the store selected by `budget.store`. Budgets
+    /// counted per instance would let every replica spend all of them, so a
+    /// `nats` store that cannot be opened is an error while budgets are set.
+    #[cfg(feature = "nats")]
+    pub async fn from_config(
+        budget: BudgetConfig,
+        policy: Option<Arc<Policy>>,
+        nats: &async_nats::Client,
+    ) -> Result<Self> {
+        let has_budgets = budget.daily_credits.is_some()
+            || budget.monthly_credits.is_some()
+            || policy.as_ref().is_some_and(|p| p.has_budgets());
+
+        let mut accountant = Self::new(budget.clone());
+        accountant.policy = policy;
+        if budget.store == UsageStoreKind::Memory {
+            if has_budgets {
+                warn!("Credit budgets are counted per server instance (budget.store = \"memory\")");
+            }
+            return Ok(accountant);
+        }
+
+        match NatsKvUsageStore::new(nats.clone(), &budget.bucket).await {
+            Ok(store) => Ok(accountant.with_store(Arc::new(store))),
+            Err(e) if has_budgets => Err(e.context(format!(
+                "Credit budgets need the usage bucket {} shared by all replicas",
+                budget.bucket
+            ))),
+            Err(e) => {
+                warn!(
+                    "NATS KV usage bucket unavailable, counting credits in memory: {:#}",
+                    e
+                );
+                Ok(accountant)
+            }
+        }
+    }
+
+    /// Applies the per-client budgets of an authorization policy on top of the server's.
+    pub fn with_policy(mut self, policy: Arc<Policy>) -> Self {
+        self.policy = Some(policy);
+        self
+    }
+
+    pub fn with_store(mut self, store: Arc<dyn UsageStore>) -> Self {
+        self.store = store;
+        self
+    }
+
+    /// Charges `credits` to the server's and the caller's budget, failing without
+    /// charging anything if either would be exceeded. Checking and charging in one
+    /// store update keeps concurrent calls, on any replica, from overshooting a
+    /// budget together.
+    pub async fn reserve(&self, caller: &Caller, credits: u64) -> Result<Reservation<'_>> {
+        let client = caller.label();
+        let client_budget = self.policy.as_ref().and_then(|p| p.budget(&client));
+
+        let state = self
+            .store
+            .update(&|state| {
+                state.roll_over();
+                check_budget(
+                    "the server",
+                    &self.budget,
+                    state.day_total,
+                    state.month_total,
+                    credits,
+                )?;
+                if let Some(budget) = &client_budget {
+                    check_budget(
+                        &format!("client {}", client),
+                        budget,
+                        state.day_by_client.get(&client).copied().unwrap_or(0),
+                        state.month_by_client.get(&client).copied().unwrap_or(0),
+                        credits,
+                    )?;
+                }
+
+                state.day_total += credits;
+                state.month_total += credits;
+                *state.day_by_client.entry(client.clone()).or_default() += credits;
+                *state.month_by_client.entry(client.clone()).or_default() += credits;
+                Ok(())
+            })
+            .await?;
+        debug!("Charged {} credit(s) to {}", credits, client);
+
+        Ok(Reservation {
+            accountant: self,
+            client,
+            day: state.day,
+            month: state.month,
+            credits,
+        })
+    }
+
+    // Gives back a reservation's credits, unless its day or month is already over
+    async fn refund(&self, reservation: &Reservation<'_>) {
+        let credits = reservation.credits;
+        let refunded = self
+            .store
+            .update(&|state| {
+                state.roll_over();
+                if state.day == reservation.day {
+                    state.day_total = state.day_total.saturating_sub(credits);
+                    if let Some(used) = state.day_by_client.get_mut(&reservation.client) {
+                        *used = used.saturating_sub(credits);
+                    }
+                }
+                if state.month == reservation.month {
+                    state.month_total = state.month_total.saturating_sub(credits);
+                    if let Some(used) = state.month_by_client.get_mut(&reservation.client) {
+                        *used = used.saturating_sub(credits);
+                    }
+                }
+                Ok(())
+            })
+            .await;
+
+        match refunded {
+            Ok(_) => debug!("Refunded {} credit(s) to {}", credits, reservation.client),
+            Err(e) => warn!(
+                "Failed to refund {} credit(s) to {}: {:#}",
+                credits, reservation.client, e
+            ),
+        }
+    }
+
+    /// Reports the server's totals and budgets. Per-client credits only list the
+    /// caller, unless the caller's policy sets `admin = true`.
+    pub async fn report(&self, caller: &Caller) -> Result<UsageReport> {
+        let client = caller.label();
+        let admin = self
+            .policy
+            .as_ref()
+            .and_then(|p| p.client(&client))
+            .is_some_and(|p| p.admin);
+
+        let mut state = self.store.load().await?;
+        state.roll_over();
+
+        let by_client = |credits: &HashMap<String, u64>| -> BTreeMap<String, u64> {
+            credits
+                .iter()
+                .filter(|(label, _)| admin || **label == client)
+                .map(|(label, used)| (label.clone(), *used))
+                .collect()
+        };
+        Ok(UsageReport {
+            day_credits: state.day_total,
+            month_credits: state.month_total,
+            daily_budget: self.budget.daily_credits,
+            monthly_budget: self.budget.monthly_credits,
+            day_credits_by_client: by_client(&state.day_by_client),
+            month_credits_by_client: by_client(&state.month_by_client),
+            day: state.day,
+            month: state.month,
+        })
+    }
+}
+
+/// Credits charged by [`UsageAccountant::reserve`] for one upstream call.
+#[must_use = "a reservation should be refunded if the call fails"]
+pub struct Reservation<'a> {
+    accountant: &'a UsageAccountant,
+    client: String,
+    day: String,
+    month: String,
+    credits: u64,
+}
+
+impl Reservation<'_> {
+    /// Returns the credits when the call they were reserved for failed.
+    pub async fn refund(self) {
+        self.accountant.refund(&self).await;
+    }
+}
+
+impl UsageState {
+    // Resets the totals when the UTC day or month changes
+    fn roll_over(&mut self) {
+        let (year, month, day) = utc_today();
+        let today = format!("{:04}-{:02}-{:02}", year, month, day);
+        if self.day == today {
+            return;
+        }
+
+        let this_month = format!("{:04}-{:02}", year, month);
+        if self.month != this_month {
+            self.month = this_month;
+            self.month_total = 0;
+            self.month_by_client.clear();
+        }
+        self.day = today;
+        self.day_total = 0;
+        self.day_by_client.clear();
+    }
+}
+
+// Counters for a single instance, lost on restart
+#[derive(Default)]
+pub struct MemoryUsageStore {
+    state: Mutex<UsageState>,
+}
+
+#[async_trait]
+impl UsageStore for MemoryUsageStore {
+    fn name(&self) -> &'static str {
+        "memory"
+    }
+
+    async fn load(&self) -> Result<UsageState> {
+        Ok(self.state.lock().unwrap().clone())
+    }
+
+    async fn update(&self, change: &UsageChange<'_>) -> Result<UsageState> {
+        let mut state = self.state.lock().unwrap();
+        let mut next = state.clone();
+        change(&mut next)?;
+        *state = next.clone();
+        Ok(next)
+    }
+}
+
+/// Tavily's usage answer for one configured key, or why it could not be read.
+#[derive(Debug, Clone, Serialize)]
+pub struct KeyUsageReport {
+    pub label: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub usage: Option<TavilyUsageResponse>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+// Reads key and plan usage from Tavily's usage endpoint for every configured key
+pub struct AccountUsage {
+    client: Arc<TavilyClient>,
+    cache_ttl: Duration,
+    warn_below_credits: Option<u64>,
+    cached: tokio::sync::Mutex<Option<(Instant, Vec<KeyUsageReport>)>>,
+}
+
+impl AccountUsage {
+    pub fn new(client: Arc<TavilyClient>, config: &UsageToolConfig) -> Self {
+        Self {
+            client,
+            cache_ttl: Duration::from_secs(config.cache_secs),
+            warn_below_credits: config.warn_below_credits,
+            cached: tokio::sync::Mutex::new(None),
+        }
+    }
+
+    /// Returns the usage of each key, reusing a recent answer unless `refresh` is set.
+    /// Keys are queried one by one, since Tavily reports the usage of the key that asks.
+    pub async fn fetch(&self, refresh: bool) -> Vec<KeyUsageReport> {
+        let mut cached = self.cached.lock().await;
+        if !refresh
+            && let Some((fetched_at, usage)) = cached.as_ref()
+            && fetched_at.elapsed() < self.cache_ttl
+        {
+            debug!("Using cached Tavily account usage");
+            return usage.clone();
+        }
+
+        let keys = self.client.keys().keys();
+        let answers = join_all(keys.iter().map(|key| {
+            self.client
+                .get_with_key::<TavilyUsageResponse>("usage", key)
+        }))
+        .await;
+
+        let mut reports = Vec::with_capacity(keys.len());
+        for (key, answer) in keys.iter().zip(answers) {
+            reports.push(match answer {
+                Ok(usage) => {
+                    self.warn_if_low(&key.label, &usage);
+                    KeyUsageReport {
+                        label: key.label.clone(),
+                        usage: Some(usage),
+                        error: None,
+                    }
+                }
+                Err(e) => {
+                    warn!("Failed to fetch Tavily usage for key {}: {}", key.label, e);
+                    KeyUsageReport {
+                        label: key.label.clone(),
+                        usage: None,
+                        error: Some(e.to_string()),
+                    }
+                }
+            });
+        }
+
+        // Failed answers are retried on the next call instead of being cached
+        if reports.iter().all(|report| report.error.is_none()) {
+            *cached = Some((Instant::now(), reports.clone()));
+        }
+        reports
+    }
+
+    fn warn_if_low(&self, label: &str, usage: &TavilyUsageResponse) {
+        if let Some(threshold) = self.warn_below_credits
+            && let Some(remaining) = usage.remaining_credits()
+            && remaining < threshold
+        {
+            warn!(
+                "Only {} Tavily credits remaining for key {} (warning threshold: {})",
+                remaining, label, threshold
+            );
+        }
+    }
+
+    /// Requests, failures and cooldowns of each configured API key.
+    pub fn keys(&self) -> Vec<KeyStatus> {
+        self.client.keys().status()
+    }
+}
+
+fn check_budget(
+    owner: &str,
+    budget: &BudgetConfig,
+    day_used: u64,
+    month_used: u64,
+    credits: u64,
+) -> Result<()> {
+    for (period, used, limit) in [
+        ("daily", day_used, budget.daily_credits),
+        ("monthly", month_used, budget.monthly_credits),
+    ] {
+        if let Some(limit) = limit
+            && used + credits > limit
+        {
+            warn!(
+                "Rejecting call: {} credit budget of {} for {} exhausted ({} used)",
+                period, limit, owner, used
+            );
+            return Err(anyhow::anyhow!(
+                "Tavily credit budget exhausted for {}: {} of {} {} credits used",
+                owner,
+                used,
+                limit,
+                period
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn utc_today() -> (i64, u32, u32) {
+    civil_date(unix_millis() / 1000)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::policy::ClientPolicy;
+
+    fn caller(client_id: &str) -> Caller {
+        Caller {
+            client_id: Some(client_id.to_string()),
+            ..Caller::default()
+        }
+    }
+
+    fn accountant(daily_credits: u64) -> UsageAccountant {
+        UsageAccountant::new(BudgetConfig {
+            daily_credits: Some(daily_credits),
+            ..BudgetConfig::default()
+        })
+    }
+
+    #[tokio::test]
+    async fn reservations_count_against_the_budget_until_refunded() {
+        let usage = accountant(3);
+
+        let first = usage.reserve(&caller("a"), 2).await.unwrap();
+        // A second call in flight cannot spend the credits the first has reserved
+        assert!(usage.reserve(&caller("b"), 2).await.is_err());
+
+        first.refund().await;
+        let _second = usage.reserve(&caller("b"), 2).await.unwrap();
+        let report = usage.report(&caller("b")).await.unwrap();
+        assert_eq!(report.day_credits, 2);
+        assert_eq!(report.day_credits_by_client.get("a"), None);
+    }
+
+    #[tokio::test]
+    async fn accountants_sharing_a_store_share_the_budget() {
+        // Stands in for replicas counting in the same KV bucket
+        let store: Arc<dyn UsageStore> = Arc::new(MemoryUsageStore::default());
+        let first = accountant(3).with_store(store.clone());
+        let second = accountant(3).with_store(store);
+
+        let _a = first.reserve(&caller("a"), 2).await.unwrap();
+        assert!(second.reserve(&caller("b"), 2).await.is_err());
+        assert_eq!(second.report(&caller("b")).await.unwrap().day_credits, 2);
+    }
+
+    #[tokio::test]
+    async fn rejected_reservations_charge_nothing() {
+        let usage = accountant(1);
+
+        assert!(usage.reserve(&caller("a"), 2).await.is_err());
+        assert_eq!(usage.report(&caller("a")).await.unwrap().day_credits, 0);
+    }
+
+    #[tokio::test]
+    async fn reports_show_other_clients_only_to_admins() {
+        let mut policy = Policy::default();
+        policy.clients.insert(
+            "operator".to_string(),
+            ClientPolicy {
+                admin: true,
+                ..ClientPolicy::default()
+            },
+        );
+        let usage = accountant(100).with_policy(Arc::new(policy));
+        let _a = usage.reserve(&caller("a"), 1).await.unwrap();
+        let _b = usage.reserve(&caller("b"), 2).await.unwrap();
+
+        let report = usage.report(&caller("a")).await.unwrap();
+        assert_eq!(report.day_credits, 3);
+        assert_eq!(
+            report.day_credits_by_client,
+            BTreeMap::from([("a".to_string(), 1)])
+        );
+
+        let report = usage.report(&caller("operator")).await.unwrap();
+        assert_eq!(report.day_credits_by_client.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn monthly_budgets_are_enforced() {
+        let usage = UsageAccountant::new(BudgetConfig {
+            monthly_credits: Some(2),
+            ..BudgetConfig::default()
+        });
+
+        let _first = usage.reserve(&caller("a"), 2).await.unwrap();
+        let error = usage
+            .reserve(&caller("a"), 1)
+            .await
+            .err()
+            .unwrap()
+            .to_string();
+        assert!(error.contains("the server: 2 of 2 monthly"), "{}", error);
+    }
+
+    #[tokio::test]
+    async fn client_budgets_apply_per_client() {
+        let mut policy = Policy::default();
+        policy.clients.insert(
+            "a".to_string(),
+            ClientPolicy {
+                daily_credits: Some(1),
+                ..ClientPolicy::default()
+            },
+        );
+        let usage = accountant(100).with_policy(Arc::new(policy));
+
+        let _a = usage.reserve(&caller("a"), 1).await.unwrap();
+        let error = usage
+            .reserve(&caller("a"), 1)
+            .await
+            .err()
+            .unwrap()
+            .to_string();
+        assert!(error.contains("client a: 1 of 1 daily"), "{}", error);
+        let _b = usage.reserve(&caller("b"), 5).await.unwrap();
+        assert_eq!(usage.report(&caller("a")).await.unwrap().day_credits, 6);
+    }
+}
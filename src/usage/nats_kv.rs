@@ -0,0 +1,99 @@
+use anyhow::{Context, Result};
+use async_nats::jetstream::{self, kv};
+use async_trait::async_trait;
+use tracing::{debug, info};
+
+use super::{UsageChange, UsageState, UsageStore};
+
+/// Key of the one entry holding every counter, so a reservation checks and
+/// charges the server's and the client's totals in a single write.
+const COUNTERS_KEY: &str = "counters";
+
+/// How often an update is retried when other replicas keep writing first.
+const MAX_ATTEMPTS: usize = 10;
+
+// Counters stored in a JetStream KV bucket, updated with compare-and-set so
+// every replica charges the same budget and totals survive restarts
+pub struct NatsKvUsageStore {
+    store: kv::Store,
+}
+
+impl NatsKvUsageStore {
+    /// Opens the bucket, creating it if needed.
+    pub async fn new(client: async_nats::Client, bucket: &str) -> Result<Self> {
+        let jetstream = jetstream::new(client);
+
+        let store = match jetstream.get_key_value(bucket).await {
+            Ok(store) => store,
+            Err(_) => jetstream
+                .create_key_value(kv::Config {
+                    bucket: bucket.to_string(),
+                    description: "Tavily MCP credit usage".to_string(),
+                    history: 1,
+                    ..Default::default()
+                })
+                .await
+                .with_context(|| format!("Failed to create usage bucket {}", bucket))?,
+        };
+
+        info!("Counting credits in NATS KV bucket {}", bucket);
+        Ok(Self { store })
+    }
+
+    // The stored counters with the revision to update, zero if the key was never written
+    async fn read(&self) -> Result<(UsageState, u64)> {
+        let entry = self
+            .store
+            .entry(COUNTERS_KEY)
+            .await
+            .with_context(|| format!("Failed to read usage bucket {}", self.store.name))?;
+
+        match entry {
+            Some(entry) if entry.operation == kv::Operation::Put => {
+                let state = serde_json::from_slice(&entry.value)
+                    .context("Failed to parse stored usage counters")?;
+                Ok((state, entry.revision))
+            }
+            Some(entry) => Ok((UsageState::default(), entry.revision)),
+            None => Ok((UsageState::default(), 0)),
+        }
+    }
+}
+
+#[async_trait]
+impl UsageStore for NatsKvUsageStore {
+    fn name(&self) -> &'static str {
+        "nats-kv"
+    }
+
+    async fn load(&self) -> Result<UsageState> {
+        Ok(self.read().await?.0)
+    }
+
+    async fn update(&self, change: &UsageChange<'_>) -> Result<UsageState> {
+        for attempt in 1..=MAX_ATTEMPTS {
+            let (mut state, revision) = self.read().await?;
+            change(&mut state)?;
+            let value = serde_json::to_vec(&state)?;
+
+            // Fails if another replica wrote since the read, which then starts over
+            let written = self
+                .store
+                .update(COUNTERS_KEY, value.into(), revision)
+                .await;
+            match written {
+                Ok(_) => return Ok(state),
+                Err(e) => debug!(
+                    "Usage counters changed during update (attempt {}): {}",
+                    attempt, e
+                ),
+            }
+        }
+
+        Err(anyhow::anyhow!(
+            "Usage counters in bucket {} kept changing, gave up after {} attempts",
+            self.store.name,
+            MAX_ATTEMPTS
+        ))
+    }
+}
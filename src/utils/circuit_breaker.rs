@@ -0,0 +1,395 @@
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+use crate::config::CircuitBreakerConfig;
+use crate::metrics::metrics;
+
+/// Returned instead of calling Tavily while an endpoint's breaker is open.
+/// Callers can find it with `anyhow::Error::downcast_ref`.
+#[derive(Debug, Clone)]
+pub struct UpstreamUnavailable {
+    pub endpoint: String,
+    pub retry_after: Duration,
+}
+
+impl fmt::Display for UpstreamUnavailable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Tavily {} is unavailable, retry in {}s",
+            self.endpoint,
+            self.retry_after.as_secs().max(1)
+        )
+    }
+}
+
+impl std::error::Error for UpstreamUnavailable {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum BreakerState {
+    Closed,
+    Open,
+    HalfOpen,
+}
+
+impl BreakerState {
+    fn gauge(self) -> i64 {
+        match self {
+            BreakerState::Closed => 0,
+            BreakerState::HalfOpen => 1,
+            BreakerState::Open => 2,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct BreakerStatus {
+    pub endpoint: String,
+    pub state: BreakerState,
+    pub requests: u32,
+    pub failures: u32,
+    /// Seconds until an open breaker lets a probe request through.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub open_remaining_secs: Option<u64>,
+}
+
+// Per-endpoint breakers that stop calling Tavily while an endpoint keeps failing
+pub struct CircuitBreakers {
+    config: CircuitBreakerConfig,
+    breakers: Mutex<HashMap<String, Breaker>>,
+}
+
+struct Breaker {
+    state: BreakerState,
+    window_start: Instant,
+    requests: u32,
+    failures: u32,
+    open_until: Instant,
+    probes: u32,
+}
+
+impl Breaker {
+    fn new() -> Self {
+        let now = Instant::now();
+        Self {
+            state: BreakerState::Closed,
+            window_start: now,
+            requests: 0,
+            failures: 0,
+            open_until: now,
+            probes: 0,
+        }
+    }
+
+    fn reset_window(&mut self, now: Instant) {
+        self.window_start = now;
+        self.requests = 0;
+        self.failures = 0;
+    }
+}
+
+impl CircuitBreakers {
+    pub fn new(config: &CircuitBreakerConfig) -> Self {
+        Self {
+            config: config.clone(),
+            breakers: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Admits a request to `endpoint`, or fails fast while its breaker is open.
+    /// Once the open period is over, up to `half_open_probes` requests are let
+    /// through to find out whether the endpoint has recovered.
+    pub fn allow(&self, endpoint: &str) -> Result<(), UpstreamUnavailable> {
+        if !self.config.enabled {
+            return Ok(());
+        }
+
+        let mut breakers = self.breakers.lock().unwrap();
+        let breaker = breakers
+            .entry(endpoint.to_string())
+            .or_insert_with(Breaker::new);
+        let now = Instant::now();
+
+        // Probes that never report back are given up on after another open period
+        if breaker.state != BreakerState::Closed && now >= breaker.open_until {
+            if breaker.state == BreakerState::Open {
+                info!("Circuit breaker for {} is half-open, probing", endpoint);
+            }
+            breaker.state = BreakerState::HalfOpen;
+            breaker.probes = 0;
+            breaker.open_until = now + self.config.open_duration();
+            metrics().set_breaker_state(endpoint, breaker.state.gauge());
+        }
+
+        let admitted = match breaker.state {
+            BreakerState::Closed => true,
+            BreakerState::HalfOpen if breaker.probes < self.config.half_open_probes.max(1) => {
+                breaker.probes += 1;
+                true
+            }
+            BreakerState::HalfOpen | BreakerState::Open => false,
+        };
+
+        if admitted {
+            return Ok(());
+        }
+
+        metrics().record_breaker_rejection(endpoint);
+        Err(UpstreamUnavailable {
+            endpoint: endpoint.to_string(),
+            retry_after: breaker.open_until.saturating_duration_since(now),
+        })
+    }
+
+    /// Records the outcome of an admitted request. `failed` should only be set
+    /// for failures of the upstream itself, such as 5xx responses and timeouts.
+    pub fn record(&self, endpoint: &str, failed: bool) {
+        if !self.config.enabled {
+            return;
+        }
+
+        let mut breakers = self.breakers.lock().unwrap();
+        let Some(breaker) = breakers.get_mut(endpoint) else {
+            return;
+        };
+        let now = Instant::now();
+
+        match breaker.state {
+            BreakerState::HalfOpen if failed => {
+                warn!("Probe to {} failed, circuit breaker reopened", endpoint);
+                self.open(endpoint, breaker, now);
+            }
+            BreakerState::HalfOpen => {
+                info!(
+                    "Circuit breaker for {} closed, endpoint recovered",
+                    endpoint
+                );
+                breaker.state = BreakerState::Closed;
+                breaker.reset_window(now);
+                metrics().set_breaker_state(endpoint, breaker.state.gauge());
+            }
+            BreakerState::Closed => {
+                if now.duration_since(breaker.window_start) >= self.config.window() {
+                    breaker.reset_window(now);
+                }
+                breaker.requests += 1;
+                if failed {
+                    breaker.failures += 1;
+                }
+
+                let failure_rate = f64::from(breaker.failures) / f64::from(breaker.requests);
+                if breaker.requests >= self.config.min_requests
+                    && failure_rate >= self.config.failure_rate
+                {
+                    warn!(
+                        "Circuit breaker for {} opened after {}/{} failed requests, pausing for {:?}",
+                        endpoint,
+                        breaker.failures,
+                        breaker.requests,
+                        self.config.open_duration()
+                    );
+                    self.open(endpoint, breaker, now);
+                }
+            }
+            // Requests admitted before the breaker opened don't change its state
+            BreakerState::Open => {}
+        }
+    }
+
+    fn open(&self, endpoint: &str, breaker: &mut Breaker, now: Instant) {
+        breaker.state = BreakerState::Open;
+        breaker.open_until = now + self.config.open_duration();
+        breaker.reset_window(now);
+        metrics().set_breaker_state(endpoint, breaker.state.gauge());
+    }
+
+    pub fn status(&self) -> Vec<BreakerStatus> {
+        let breakers = self.breakers.lock().unwrap();
+        let now = Instant::now();
+
+        let mut status: Vec<BreakerStatus> = breakers
+            .iter()
+            .map(|(endpoint, breaker)| BreakerStatus {
+                endpoint: endpoint.clone(),
+                state: breaker.state,
+                requests: breaker.requests,
+                failures: breaker.failures,
+                open_remaining_secs: (breaker.state == BreakerState::Open)
+                    .then(|| breaker.open_until.saturating_duration_since(now).as_secs()),
+            })
+            .collect();
+        status.sort_by(|a, b| a.endpoint.cmp(&b.endpoint));
+        status
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ENDPOINT: &str = "search";
+
+    // Opens after two requests with at least half of them failed
+    fn breakers(half_open_probes: u32) -> CircuitBreakers {
+        CircuitBreakers::new(&CircuitBreakerConfig {
+            enabled: true,
+            failure_rate: 0.5,
+            min_requests: 2,
+            window_secs: 60,
+            open_secs: 60,
+            half_open_probes,
+        })
+    }
+
+    fn state(breakers: &CircuitBreakers) -> BreakerState {
+        breakers
+            .status()
+            .into_iter()
+            .find(|status| status.endpoint == ENDPOINT)
+            .map(|status| status.state)
+            .unwrap()
+    }
+
+    fn open(breakers: &CircuitBreakers) {
+        for _ in 0..2 {
+            breakers.allow(ENDPOINT).unwrap();
+            breakers.record(ENDPOINT, true);
+        }
+        assert_eq!(state(breakers), BreakerState::Open);
+    }
+
+    // Ends the open period without waiting for it
+    fn expire(breakers: &CircuitBreakers) {
+        let mut breakers = breakers.breakers.lock().unwrap();
+        breakers.get_mut(ENDPOINT).unwrap().open_until = Instant::now();
+    }
+
+    #[test]
+    fn stays_closed_below_min_requests() {
+        let breakers = breakers(1);
+
+        breakers.allow(ENDPOINT).unwrap();
+        breakers.record(ENDPOINT, true);
+        assert_eq!(state(&breakers), BreakerState::Closed);
+    }
+
+    #[test]
+    fn stays_closed_below_the_failure_rate() {
+        let breakers = breakers(1);
+
+        for failed in [false, false, true] {
+            breakers.allow(ENDPOINT).unwrap();
+            breakers.record(ENDPOINT, failed);
+        }
+        assert_eq!(state(&breakers), BreakerState::Closed);
+    }
+
+    #[test]
+    fn closed_opens_when_the_failure_rate_is_reached() {
+        let breakers = breakers(1);
+        open(&breakers);
+
+        let error = breakers.allow(ENDPOINT).unwrap_err();
+        assert_eq!(error.endpoint, ENDPOINT);
+        assert!(error.retry_after > Duration::from_secs(50));
+    }
+
+    #[test]
+    fn open_becomes_half_open_after_the_open_period() {
+        let breakers = breakers(1);
+        open(&breakers);
+        expire(&breakers);
+
+        breakers.allow(ENDPOINT).unwrap();
+        assert_eq!(state(&breakers), BreakerState::HalfOpen);
+    }
+
+    #[test]
+    fn half_open_admits_only_its_probes() {
+        let breakers = breakers(2);
+        open(&breakers);
+        expire(&breakers);
+
+        assert!(breakers.allow(ENDPOINT).is_ok());
+        assert!(breakers.allow(ENDPOINT).is_ok());
+        assert!(breakers.allow(ENDPOINT).is_err());
+        assert_eq!(state(&breakers), BreakerState::HalfOpen);
+    }
+
+    #[test]
+    fn unanswered_probes_are_replaced_after_another_open_period() {
+        let breakers = breakers(1);
+        open(&breakers);
+        expire(&breakers);
+        breakers.allow(ENDPOINT).unwrap();
+        assert!(breakers.allow(ENDPOINT).is_err());
+
+        expire(&breakers);
+        assert!(breakers.allow(ENDPOINT).is_ok());
+    }
+
+    #[test]
+    fn successful_probe_closes_the_breaker() {
+        let breakers = breakers(1);
+        open(&breakers);
+        expire(&breakers);
+
+        breakers.allow(ENDPOINT).unwrap();
+        breakers.record(ENDPOINT, false);
+        assert_eq!(state(&breakers), BreakerState::Closed);
+
+        // The window starts over, so one more failure doesn't reopen it
+        breakers.allow(ENDPOINT).unwrap();
+        breakers.record(ENDPOINT, true);
+        assert_eq!(state(&breakers), BreakerState::Closed);
+    }
+
+    #[test]
+    fn failed_probe_reopens_the_breaker() {
+        let breakers = breakers(1);
+        open(&breakers);
+        expire(&breakers);
+
+        breakers.allow(ENDPOINT).unwrap();
+        breakers.record(ENDPOINT, true);
+        assert_eq!(state(&breakers), BreakerState::Open);
+        assert!(breakers.allow(ENDPOINT).is_err());
+    }
+
+    #[test]
+    fn outcomes_recorded_while_open_are_ignored() {
+        let breakers = breakers(1);
+        open(&breakers);
+
+        breakers.record(ENDPOINT, false);
+        assert_eq!(state(&breakers), BreakerState::Open);
+    }
+
+    #[test]
+    fn endpoints_have_their_own_breakers() {
+        let breakers = breakers(1);
+        open(&breakers);
+
+        assert!(breakers.allow("extract").is_ok());
+    }
+
+    #[test]
+    fn disabled_breakers_admit_everything() {
+        let breakers = CircuitBreakers::new(&CircuitBreakerConfig {
+            enabled: false,
+            min_requests: 1,
+            ..CircuitBreakerConfig::default()
+        });
+
+        for _ in 0..5 {
+            breakers.allow(ENDPOINT).unwrap();
+            breakers.record(ENDPOINT, true);
+        }
+        assert!(breakers.status().is_empty());
+    }
+}
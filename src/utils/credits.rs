@@ -0,0 +1,28 @@
+use serde_json::Value;
+
+/// Estimates the Tavily credits a request to `endpoint` costs, based on the
+/// published pricing: search costs 1 credit (2 for advanced depth), extract
+/// costs 1 credit per 5 URLs (2 per 5 for advanced depth).
+pub fn estimate_credits(endpoint: &str, params: &Value) -> u64 {
+    let advanced = |field: &str| params.get(field).and_then(|d| d.as_str()) == Some("advanced");
+
+    match endpoint {
+        "search" => {
+            if advanced("search_depth") {
+                2
+            } else {
+                1
+            }
+        }
+        "extract" => {
+            let urls = match params.get("urls") {
+                Some(Value::Array(urls)) => urls.len() as u64,
+                Some(Value::String(_)) => 1,
+                _ => 0,
+            };
+            let per_batch = if advanced("extract_depth") { 2 } else { 1 };
+            urls.div_ceil(5) * per_batch
+        }
+        _ => 0,
+    }
+}
@@ -1,5 +1,5 @@
-
 use crate::models::tavily::{TavilyExtractResponse, TavilyResponse};
+use crate::utils::time::format_utc;
 
 // Helper function to format Tavily API search responses
 pub fn format_tavily_results(response: &TavilyResponse) -> String {
@@ -23,7 +23,10 @@ pub fn format_tavily_results(response: &TavilyResponse) -> String {
     for result in &response.results {
         output.push(format!("\nTitle: {}", result.title));
         output.push(format!("URL: {}", result.url));
-        output.push(format!("Content: {}", result.content));
+
+        if let Some(content) = &result.content {
+            output.push(format!("Content: {}", content));
+        }
 
         if let Some(raw_content) = &result.raw_content {
             output.push(format!("Raw Content: {}", raw_content));
@@ -56,10 +59,17 @@ pub fn format_tavily_extract_results(response: &TavilyExtractResponse) -> String
         }
     }
 
-    output.push(format!(
-        "\nResponse Time: {} seconds",
-        response.response_time
-    ));
+    if let Some(response_time) = response.response_time {
+        output.push(format!("\nResponse Time: {} seconds", response_time));
+    }
 
     output.join("\n")
 }
+
+// Prepended to results served from an expired cache entry
+pub fn format_stale_notice(fetched_at: u64) -> String {
+    format!(
+        "Note: Tavily is currently unavailable. These are cached results fetched at {} and may be out of date.\n",
+        format_utc(fetched_at)
+    )
+}
@@ -1 +1,9 @@
+pub mod circuit_breaker;
+pub mod credits;
 pub mod formatter;
+pub mod params;
+pub mod rate_limiter;
+pub mod redact;
+pub mod response_format;
+pub mod schema;
+pub mod time;
@@ -0,0 +1,51 @@
+use serde_json::{Map, Value};
+use tracing::debug;
+
+use crate::config::ToolConfig;
+
+// Server-side parameter overrides for a tool
+#[derive(Debug, Clone, Default)]
+pub struct ParamOverrides {
+    /// Applied when the caller does not set the parameter.
+    pub defaults: Map<String, Value>,
+    /// Always applied, replacing whatever the caller sent.
+    pub forced: Map<String, Value>,
+}
+
+impl ParamOverrides {
+    pub fn from_config(config: &ToolConfig) -> Self {
+        Self {
+            defaults: config.defaults.clone(),
+            forced: config.forced.clone(),
+        }
+    }
+
+    /// Fills in parameters the caller left unset from the configured defaults.
+    pub fn apply_defaults(&self, params: &mut Value) {
+        let Some(params) = params.as_object_mut() else {
+            return;
+        };
+
+        for (key, value) in &self.defaults {
+            params.entry(key.clone()).or_insert_with(|| value.clone());
+        }
+    }
+
+    /// Overwrites parameters with the forced values.
+    pub fn apply_forced(&self, params: &mut Value) {
+        let Some(params) = params.as_object_mut() else {
+            return;
+        };
+
+        for (key, value) in &self.forced {
+            if let Some(previous) = params.insert(key.clone(), value.clone())
+                && previous != *value
+            {
+                debug!(
+                    "Forced parameter {} overrides caller value {}",
+                    key, previous
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,89 @@
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tokio::time::{Instant, sleep_until};
+
+// Spaces requests evenly so that at most `requests_per_second` start each second
+pub struct RateLimiter {
+    interval: Duration,
+    burst: u32,
+    next_slot: Mutex<Instant>,
+}
+
+impl RateLimiter {
+    pub fn new(requests_per_second: f64) -> Self {
+        Self {
+            interval: Duration::from_secs_f64(1.0 / requests_per_second.max(f64::EPSILON)),
+            burst: 1,
+            next_slot: Mutex::new(Instant::now()),
+        }
+    }
+
+    /// Lets `try_acquire` admit up to `burst` requests back to back.
+    pub fn with_burst(mut self, burst: u32) -> Self {
+        self.burst = burst.max(1);
+        self
+    }
+
+    /// Waits for the next free slot and returns how long the caller waited.
+    pub async fn acquire(&self) -> Duration {
+        let slot = {
+            let mut next_slot = self.next_slot.lock().await;
+            let now = Instant::now();
+            let slot = (*next_slot).max(now);
+            *next_slot = slot + self.interval;
+            slot
+        };
+
+        let waited = slot.saturating_duration_since(Instant::now());
+        if !waited.is_zero() {
+            sleep_until(slot).await;
+        }
+        waited
+    }
+
+    /// Takes a slot without waiting, returning false when the limit is exceeded.
+    pub async fn try_acquire(&self) -> bool {
+        let mut next_slot = self.next_slot.lock().await;
+        let now = Instant::now();
+        let slot = (*next_slot).max(now);
+        if slot > now + self.interval * (self.burst - 1) {
+            return false;
+        }
+        *next_slot = slot + self.interval;
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn try_acquire_admits_a_burst_then_rejects() {
+        let limiter = RateLimiter::new(1.0).with_burst(3);
+
+        for _ in 0..3 {
+            assert!(limiter.try_acquire().await);
+        }
+        assert!(!limiter.try_acquire().await);
+    }
+
+    #[tokio::test]
+    async fn try_acquire_admits_one_request_without_a_burst() {
+        let limiter = RateLimiter::new(1.0);
+
+        assert!(limiter.try_acquire().await);
+        assert!(!limiter.try_acquire().await);
+    }
+
+    #[tokio::test]
+    async fn acquire_spaces_requests_by_the_interval() {
+        let limiter = RateLimiter::new(20.0);
+
+        assert!(limiter.acquire().await.is_zero());
+        let started = Instant::now();
+        let waited = limiter.acquire().await;
+        assert!(waited > Duration::from_millis(30), "{:?}", waited);
+        assert!(started.elapsed() >= waited);
+    }
+}
@@ -0,0 +1,192 @@
+use regex::Regex;
+use serde_json::Value;
+use std::borrow::Cow;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+const REDACTED: &str = "[REDACTED]";
+
+static MAX_PAYLOAD_BYTES: AtomicUsize = AtomicUsize::new(2048);
+
+// Scrubs secrets and configured fields from log output
+pub struct Redactor {
+    patterns: Vec<(Regex, String)>,
+    secrets: Vec<String>,
+}
+
+impl Redactor {
+    /// `fields` are field names whose values are always replaced, e.g. `raw_content`.
+    /// `secrets` are literal values, such as the configured API key, that must never appear.
+    pub fn new(fields: &[String], secrets: Vec<String>) -> Self {
+        let mut patterns = vec![
+            (
+                Regex::new(r"(?i)(bearer\s+)[A-Za-z0-9._~+/=-]+").unwrap(),
+                format!("${{1}}{}", REDACTED),
+            ),
+            (
+                Regex::new(r"tvly-[A-Za-z0-9_-]+").unwrap(),
+                REDACTED.to_string(),
+            ),
+        ];
+
+        for field in fields {
+            let name = regex::escape(field);
+            // JSON and Debug output: "field": "value" / field: "value"
+            patterns.push((
+                Regex::new(&format!(r#"(?i)("?{}"?\s*:\s*)"(?:[^"\\]|\\.)*""#, name)).unwrap(),
+                format!("${{1}}\"{}\"", REDACTED),
+            ));
+            // key=value pairs written by the text formatter
+            patterns.push((
+                Regex::new(&format!(r"(?i)(\b{}=)[^\s,}}]+", name)).unwrap(),
+                format!("${{1}}{}", REDACTED),
+            ));
+        }
+
+        Self {
+            patterns,
+            secrets: secrets.into_iter().filter(|s| !s.is_empty()).collect(),
+        }
+    }
+
+    pub fn redact<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        let mut output = Cow::Borrowed(text);
+
+        for secret in &self.secrets {
+            if output.contains(secret.as_str()) {
+                output = Cow::Owned(output.replace(secret.as_str(), REDACTED));
+            }
+        }
+
+        for (pattern, replacement) in &self.patterns {
+            if let Cow::Owned(replaced) = pattern.replace_all(&output, replacement.as_str()) {
+                output = Cow::Owned(replaced);
+            }
+        }
+
+        output
+    }
+}
+
+pub fn set_max_payload_bytes(max: usize) {
+    MAX_PAYLOAD_BYTES.store(max, Ordering::Relaxed);
+}
+
+/// Caps a payload before it is logged, keeping the start of it.
+pub fn truncate_payload(text: &str) -> Cow<'_, str> {
+    let max = MAX_PAYLOAD_BYTES.load(Ordering::Relaxed);
+    if text.len() <= max {
+        return Cow::Borrowed(text);
+    }
+
+    let mut end = max;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    Cow::Owned(format!(
+        "{}... [truncated {} bytes]",
+        &text[..end],
+        text.len() - end
+    ))
+}
+
+/// Replaces the values of `fields` anywhere in `value`, matching keys case-insensitively.
+pub fn redact_fields(value: &mut Value, fields: &[String]) {
+    match value {
+        Value::Object(map) => {
+            for (key, field) in map.iter_mut() {
+                if fields.iter().any(|f| f.eq_ignore_ascii_case(key)) {
+                    *field = Value::String(REDACTED.to_string());
+                } else {
+                    redact_fields(field, fields);
+                }
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                redact_fields(item, fields);
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn redactor() -> Redactor {
+        Redactor::new(
+            &["raw_content".to_string()],
+            vec!["s3cret-value".to_string(), String::new()],
+        )
+    }
+
+    #[test]
+    fn bearer_tokens_and_tavily_keys_are_redacted() {
+        let redactor = redactor();
+
+        assert_eq!(
+            redactor.redact("Authorization: Bearer abc.def-ghi"),
+            "Authorization: Bearer [REDACTED]"
+        );
+        assert_eq!(
+            redactor.redact("api_key=tvly-AbC_123 sent"),
+            "api_key=[REDACTED] sent"
+        );
+    }
+
+    #[test]
+    fn configured_fields_are_redacted_in_json_debug_and_key_value_output() {
+        let redactor = redactor();
+
+        assert_eq!(
+            redactor.redact(r#"{"raw_content": "a \"quoted\" page", "url": "x"}"#),
+            r#"{"raw_content": "[REDACTED]", "url": "x"}"#
+        );
+        assert_eq!(
+            redactor.redact(r#"Result { raw_content: "page" }"#),
+            r#"Result { raw_content: "[REDACTED]" }"#
+        );
+        assert_eq!(
+            redactor.redact("raw_content=page, url=x"),
+            "raw_content=[REDACTED], url=x"
+        );
+    }
+
+    #[test]
+    fn literal_secrets_are_redacted_and_clean_text_is_borrowed() {
+        let redactor = redactor();
+
+        assert_eq!(redactor.redact("key s3cret-value!"), "key [REDACTED]!");
+        assert!(matches!(redactor.redact("nothing here"), Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn payloads_are_truncated_on_a_char_boundary() {
+        set_max_payload_bytes(4);
+
+        assert_eq!(truncate_payload("abcd"), "abcd");
+        assert_eq!(truncate_payload("abcdef"), "abcd... [truncated 2 bytes]");
+        assert_eq!(truncate_payload("abcé"), "abc... [truncated 2 bytes]");
+
+        set_max_payload_bytes(2048);
+    }
+
+    #[test]
+    fn fields_are_redacted_at_any_depth() {
+        let mut value = json!({
+            "Raw_Content": "page",
+            "results": [{ "raw_content": "page", "url": "x" }],
+        });
+        redact_fields(&mut value, &["raw_content".to_string()]);
+
+        assert_eq!(
+            value,
+            json!({
+                "Raw_Content": "[REDACTED]",
+                "results": [{ "raw_content": "[REDACTED]", "url": "x" }],
+            })
+        );
+    }
+}
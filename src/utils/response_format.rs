@@ -0,0 +1,29 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Field of the `_meta` argument that selects how a tool renders its result.
+pub const RESPONSE_FORMAT_FIELD: &str = "response_format";
+
+/// `text` is the formatted summary meant for language models; `json` is the
+/// Tavily response itself, for programs such as `TavilyMcpClient`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ResponseFormat {
+    #[default]
+    Text,
+    Json,
+}
+
+impl ResponseFormat {
+    /// Reads `_meta.response_format`. Anything but `"json"` keeps the text output.
+    pub fn from_params(params: &Value) -> Self {
+        match params
+            .get("_meta")
+            .and_then(|meta| meta.get(RESPONSE_FORMAT_FIELD))
+            .and_then(|format| format.as_str())
+        {
+            Some("json") => ResponseFormat::Json,
+            _ => ResponseFormat::Text,
+        }
+    }
+}
@@ -0,0 +1,188 @@
+use anyhow::Result;
+use serde_json::Value;
+
+/// Checks tool arguments against the subset of JSON Schema the tools' input
+/// schemas use: `required`, and per property `type`, `enum`, `minLength`,
+/// `minimum`, `maximum`, `minItems` and the `type` of array `items`. Properties
+/// the schema doesn't describe, such as `_meta`, are left alone, and null
+/// counts as absent.
+pub fn validate(schema: &Value, params: &Value) -> Result<()> {
+    let Some(params) = params.as_object() else {
+        return Err(anyhow::anyhow!("Arguments must be an object"));
+    };
+
+    let required = schema.get("required").and_then(|r| r.as_array());
+    for name in required.into_iter().flatten().filter_map(|n| n.as_str()) {
+        if params.get(name).is_none_or(Value::is_null) {
+            return Err(anyhow::anyhow!("Missing required argument {}", name));
+        }
+    }
+
+    let Some(properties) = schema.get("properties").and_then(|p| p.as_object()) else {
+        return Ok(());
+    };
+    for (name, value) in params {
+        if let Some(property) = properties.get(name)
+            && !value.is_null()
+        {
+            check_property(name, property, value)?;
+        }
+    }
+    Ok(())
+}
+
+fn check_property(name: &str, property: &Value, value: &Value) -> Result<()> {
+    if let Some(expected) = property.get("type").and_then(|t| t.as_str())
+        && !has_type(value, expected)
+    {
+        return Err(anyhow::anyhow!("{} must be of type {}", name, expected));
+    }
+
+    if let Some(allowed) = property.get("enum").and_then(|e| e.as_array())
+        && !allowed.contains(value)
+    {
+        let allowed: Vec<String> = allowed.iter().map(|v| v.to_string()).collect();
+        return Err(anyhow::anyhow!(
+            "{} must be one of {}, got {}",
+            name,
+            allowed.join(", "),
+            value
+        ));
+    }
+
+    if let Some(text) = value.as_str()
+        && let Some(min_length) = property.get("minLength").and_then(|m| m.as_u64())
+        && (text.trim().chars().count() as u64) < min_length
+    {
+        return Err(anyhow::anyhow!(
+            "{} must be at least {} character(s) long",
+            name,
+            min_length
+        ));
+    }
+
+    if let Some(number) = value.as_f64() {
+        if let Some(minimum) = property.get("minimum").and_then(|m| m.as_f64())
+            && number < minimum
+        {
+            return Err(anyhow::anyhow!("{} must be at least {}", name, minimum));
+        }
+        if let Some(maximum) = property.get("maximum").and_then(|m| m.as_f64())
+            && number > maximum
+        {
+            return Err(anyhow::anyhow!("{} must be at most {}", name, maximum));
+        }
+    }
+
+    if let Some(items) = value.as_array() {
+        if let Some(min_items) = property.get("minItems").and_then(|m| m.as_u64())
+            && (items.len() as u64) < min_items
+        {
+            return Err(anyhow::anyhow!(
+                "{} must have at least {} item(s)",
+                name,
+                min_items
+            ));
+        }
+        if let Some(expected) = property.pointer("/items/type").and_then(|t| t.as_str())
+            && items.iter().any(|item| !has_type(item, expected))
+        {
+            return Err(anyhow::anyhow!("{} must only contain {}s", name, expected));
+        }
+    }
+    Ok(())
+}
+
+fn has_type(value: &Value, expected: &str) -> bool {
+    match expected {
+        "string" => value.is_string(),
+        "number" => value.is_number(),
+        "integer" => value.is_i64() || value.is_u64(),
+        "boolean" => value.is_boolean(),
+        "array" => value.is_array(),
+        "object" => value.is_object(),
+        _ => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn schema() -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "query": { "type": "string", "minLength": 1 },
+                "depth": { "type": "string", "enum": ["basic", "advanced"] },
+                "max_results": { "type": "number", "minimum": 5, "maximum": 20 },
+                "urls": { "type": "array", "items": { "type": "string" }, "minItems": 1 }
+            },
+            "required": ["query"]
+        })
+    }
+
+    fn error(params: Value) -> String {
+        validate(&schema(), &params).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn valid_arguments_pass() {
+        validate(
+            &schema(),
+            &json!({
+                "query": "rust",
+                "depth": "advanced",
+                "max_results": 5,
+                "urls": ["https://example.com/"],
+                "days": null,
+                "_meta": { "client_id": "agent" }
+            }),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn required_arguments_must_be_present() {
+        assert_eq!(error(json!({})), "Missing required argument query");
+        assert_eq!(
+            error(json!({ "query": null })),
+            "Missing required argument query"
+        );
+        assert_eq!(error(json!([])), "Arguments must be an object");
+    }
+
+    #[test]
+    fn types_enums_and_ranges_are_checked() {
+        assert_eq!(error(json!({ "query": 1 })), "query must be of type string");
+        assert_eq!(
+            error(json!({ "query": " " })),
+            "query must be at least 1 character(s) long"
+        );
+        assert_eq!(
+            error(json!({ "query": "rust", "depth": "deep" })),
+            "depth must be one of \"basic\", \"advanced\", got \"deep\""
+        );
+        assert_eq!(
+            error(json!({ "query": "rust", "max_results": 50 })),
+            "max_results must be at most 20"
+        );
+        assert_eq!(
+            error(json!({ "query": "rust", "max_results": 1 })),
+            "max_results must be at least 5"
+        );
+    }
+
+    #[test]
+    fn array_length_and_items_are_checked() {
+        assert_eq!(
+            error(json!({ "query": "rust", "urls": [] })),
+            "urls must have at least 1 item(s)"
+        );
+        assert_eq!(
+            error(json!({ "query": "rust", "urls": [1] })),
+            "urls must only contain strings"
+        );
+    }
+}
@@ -0,0 +1,128 @@
+//! Client authentication and policy checks through `McpServer`, with the
+//! credentials in request headers or `_meta` and the mock Tavily API upstream.
+
+use mock_tavily::MockTavily;
+use serde_json::{Map, Value, json};
+use std::sync::Arc;
+use tavily_anet_mcp::audit::AuditLog;
+use tavily_anet_mcp::auth::Authenticator;
+use tavily_anet_mcp::auth::policy::{ClientPolicy, Policy};
+use tavily_anet_mcp::cache::Cache;
+use tavily_anet_mcp::client::TavilyClient;
+use tavily_anet_mcp::config::{AuthMode, Config};
+use tavily_anet_mcp::server::McpServer;
+use tavily_anet_mcp::shutdown::Shutdown;
+use tavily_anet_mcp::tools::registry::build_tools;
+use tavily_anet_mcp::usage::UsageAccountant;
+
+const TOKEN: &str = "agent-token";
+
+// Serves the tools for one `agent` client that may only run basic searches,
+// while the server defaults to advanced ones
+async fn start() -> (MockTavily, McpServer) {
+    let mock = MockTavily::start().await.unwrap();
+
+    let mut config = Config::default();
+    config.tavily.api_key = Some("tvly-test-key".to_string());
+    config.tavily.base_url = mock.base_url();
+    config.auth.mode = AuthMode::Token;
+    config
+        .tools
+        .search
+        .defaults
+        .insert("search_depth".to_string(), json!("advanced"));
+
+    let mut policy = Policy::default();
+    policy.clients.insert(
+        "agent".to_string(),
+        ClientPolicy {
+            token: Some(TOKEN.to_string()),
+            parameters: [("search_depth".to_string(), vec![json!("basic")])].into(),
+            ..ClientPolicy::default()
+        },
+    );
+    let policy = Arc::new(policy);
+    let auth = Arc::new(Authenticator::new(&config.auth, policy.clone()).unwrap());
+
+    let client = Arc::new(
+        TavilyClient::new(
+            config.api_keys().unwrap(),
+            &config.tavily,
+            &config.retry,
+            &config.rate_limit,
+            &config.circuit_breaker,
+        )
+        .unwrap(),
+    );
+    let tools = build_tools(
+        &config.tools,
+        client,
+        Cache::disabled(),
+        AuditLog::disabled(),
+        Arc::new(UsageAccountant::new(config.budget.clone()).with_policy(policy)),
+        Some(auth.clone()),
+    );
+
+    (mock, McpServer::new(tools, Some(auth), &Shutdown::new()))
+}
+
+async fn search(server: &McpServer, arguments: Value, headers: &[(&str, &str)]) -> Value {
+    let request = json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "callTool",
+        "params": { "name": "tavily-search", "arguments": arguments },
+    });
+    let meta: Map<String, Value> = headers
+        .iter()
+        .map(|(field, value)| (field.to_string(), json!(value)))
+        .collect();
+    server.handle(request, meta).await.unwrap()
+}
+
+#[tokio::test]
+async fn policy_applies_to_server_defaults() {
+    let (mock, server) = start().await;
+
+    // The caller sends nothing the policy forbids, but the default would be advanced
+    let response = search(
+        &server,
+        json!({ "query": "rust" }),
+        &[("authorization", TOKEN)],
+    )
+    .await;
+    let message = response["error"]["message"].as_str().unwrap();
+    assert!(
+        message.contains("may not use search_depth = \"advanced\""),
+        "{}",
+        message
+    );
+    assert!(mock.requests().is_empty());
+}
+
+#[tokio::test]
+async fn header_credentials_are_accepted() {
+    let (mock, server) = start().await;
+
+    let response = search(
+        &server,
+        json!({ "query": "rust", "search_depth": "basic" }),
+        &[("authorization", &format!("Bearer {}", TOKEN))],
+    )
+    .await;
+    assert!(response.get("error").is_none(), "{}", response);
+
+    let sent = &mock.requests_to("search")[0].body;
+    assert_eq!(sent["search_depth"], "basic");
+    assert!(sent.get("_meta").is_none());
+}
+
+#[tokio::test]
+async fn calls_without_credentials_are_rejected() {
+    let (mock, server) = start().await;
+
+    let response = search(&server, json!({ "query": "rust" }), &[]).await;
+    let message = response["error"]["message"].as_str().unwrap();
+    assert!(message.contains("Missing credentials"), "{}", message);
+    assert!(mock.requests().is_empty());
+}
@@ -0,0 +1,154 @@
+//! The `check`, `list-tools` and `call` commands of the server binary against
+//! the mock Tavily API. No network access or API key is needed. Tool behaviour
+//! is covered in-process by `tests/tools.rs`.
+
+mod common;
+
+use common::{API_KEY, Harness};
+use serde_json::json;
+
+#[tokio::test]
+async fn check_asks_every_key_for_its_usage() {
+    let harness = Harness::start().await;
+
+    let run = harness
+        .run_with_env(&["check"], &[("TAVILY_API_KEYS", "backup:tvly-backup-key")])
+        .await;
+    assert!(run.success, "{}", run.stderr);
+    assert!(
+        run.stdout.contains("API key key-1 accepted"),
+        "{}",
+        run.stdout
+    );
+    assert!(
+        run.stdout.contains("API key backup accepted"),
+        "{}",
+        run.stdout
+    );
+
+    // The usage endpoint is free, a search would spend a credit per check
+    assert!(harness.mock.requests_to("search").is_empty());
+    let mut keys: Vec<String> = harness
+        .mock
+        .requests_to("usage")
+        .into_iter()
+        .filter_map(|request| request.api_key)
+        .collect();
+    keys.sort();
+    assert_eq!(keys, ["tvly-backup-key", API_KEY]);
+}
+
+#[tokio::test]
+async fn check_fails_when_any_key_is_rejected() {
+    let harness = Harness::start().await;
+
+    let run = harness
+        .run_with_env(&["check"], &[("TAVILY_API_KEYS", "revoked:tvly-mock-401")])
+        .await;
+    assert!(!run.success);
+    assert!(
+        run.stdout.contains("API key key-1 accepted"),
+        "{}",
+        run.stdout
+    );
+    assert!(
+        run.stdout.contains("API key revoked failed"),
+        "{}",
+        run.stdout
+    );
+    assert!(
+        run.stderr.contains("1 of 2 API key(s) failed"),
+        "{}",
+        run.stderr
+    );
+}
+
+#[tokio::test]
+async fn check_validates_the_auth_configuration() {
+    let harness =
+        Harness::with_config("[auth]\nmode = \"token\"\npolicy_file = \"missing.toml\"").await;
+    let run = harness.run(&["check", "--offline"]).await;
+    assert!(!run.success);
+    assert!(run.stderr.contains("missing.toml"), "{}", run.stderr);
+
+    let harness = Harness::with_config("[auth]\nmode = \"jwt\"").await;
+    let run = harness.run(&["check", "--offline"]).await;
+    assert!(!run.success);
+    assert!(
+        run.stderr.contains("needs auth.jwt_secret"),
+        "{}",
+        run.stderr
+    );
+    assert!(harness.mock.requests().is_empty());
+}
+
+#[tokio::test]
+async fn list_tools_prints_the_tools_as_json() {
+    let harness = Harness::start().await;
+
+    let run = harness.run(&["list-tools"]).await;
+    assert!(run.success, "{}", run.stderr);
+    let tools = run.json();
+    let names: Vec<&str> = tools
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|tool| tool["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(names, ["tavily-search", "tavily-extract", "tavily-usage"]);
+    assert!(harness.mock.requests().is_empty());
+}
+
+#[tokio::test]
+async fn configuration_warnings_are_logged() {
+    let harness = Harness::start().await;
+
+    let run = harness
+        .run_with_env(&["list-tools"], &[("MCP_CACHE_BACKEND", "redis")])
+        .await;
+    assert!(run.success, "{}", run.stderr);
+    assert!(
+        run.stderr
+            .contains("Ignoring unknown MCP_CACHE_BACKEND 'redis'"),
+        "{}",
+        run.stderr
+    );
+}
+
+#[tokio::test]
+async fn call_prints_the_tool_result() {
+    let harness = Harness::start().await;
+
+    let run = harness
+        .call("tavily-search", json!({ "query": "rust" }))
+        .await;
+    assert!(run.success, "{}", run.stderr);
+    assert!(run.stdout.contains("Detailed Results:"), "{}", run.stdout);
+    assert_eq!(harness.mock.requests_to("search").len(), 1);
+}
+
+#[tokio::test]
+async fn call_rejects_arguments_that_are_not_an_object() {
+    let harness = Harness::start().await;
+
+    for args in ["[1, 2]", "{"] {
+        let run = harness
+            .run(&["call", "tavily-search", "--args", args])
+            .await;
+        assert!(!run.success);
+        assert!(
+            run.stderr.contains("--args must be a JSON object"),
+            "{}",
+            run.stderr
+        );
+    }
+
+    let run = harness.call("tavily-crawl", json!({})).await;
+    assert!(!run.success);
+    assert!(
+        run.stderr.contains("Unknown tool 'tavily-crawl'"),
+        "{}",
+        run.stderr
+    );
+    assert!(harness.mock.requests().is_empty());
+}
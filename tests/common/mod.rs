@@ -0,0 +1,195 @@
+//! Runs the server binary, or `McpServer` in-process, against an in-process
+//! mock Tavily API.
+
+#![allow(dead_code)]
+
+use mock_tavily::MockTavily;
+use serde_json::Value;
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use tavily_anet_mcp::audit::AuditLog;
+use tavily_anet_mcp::cache::Cache;
+use tavily_anet_mcp::client::TavilyClient;
+use tavily_anet_mcp::config::Config;
+use tavily_anet_mcp::mcp_client::{InProcessTransport, TavilyMcpClient};
+use tavily_anet_mcp::server::McpServer;
+use tavily_anet_mcp::shutdown::Shutdown;
+use tavily_anet_mcp::tools::registry::build_tools;
+use tavily_anet_mcp::usage::UsageAccountant;
+use tokio::process::{Child, Command};
+
+pub const API_KEY: &str = "tvly-test-key";
+
+static NEXT_DIR: AtomicUsize = AtomicUsize::new(0);
+
+/// The output of one run of the binary.
+pub struct Run {
+    pub success: bool,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+impl Run {
+    pub fn json(&self) -> Value {
+        serde_json::from_str(&self.stdout)
+            .unwrap_or_else(|e| panic!("stdout is not JSON ({}): {}", e, self.stdout))
+    }
+}
+
+// A mock Tavily plus a scratch directory holding the server's config file
+pub struct Harness {
+    pub mock: MockTavily,
+    dir: PathBuf,
+}
+
+impl Harness {
+    pub async fn start() -> Self {
+        Self::with_config("").await
+    }
+
+    /// `extra` is appended to the generated config file, e.g. a `[circuit_breaker]` section.
+    pub async fn with_config(extra: &str) -> Self {
+        let mock = MockTavily::start().await.expect("mock Tavily starts");
+        let dir = std::env::temp_dir().join(format!(
+            "tavily-mcp-test-{}-{}",
+            std::process::id(),
+            NEXT_DIR.fetch_add(1, Ordering::Relaxed)
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let config = format!(
+            r#"[tavily]
+api_key = "{api_key}"
+base_url = "{base_url}"
+timeout_secs = 5
+
+[retry]
+max_retries = 2
+initial_backoff_ms = 10
+max_backoff_ms = 20
+
+[cache]
+backend = "none"
+
+[logging]
+level = "warn"
+
+{extra}
+"#,
+            api_key = API_KEY,
+            base_url = mock.base_url(),
+        );
+        std::fs::write(dir.join("config.toml"), config).unwrap();
+
+        Self { mock, dir }
+    }
+
+    /// Runs the binary with `args`, isolated from the caller's environment and `.env`.
+    pub async fn run(&self, args: &[&str]) -> Run {
+        self.run_with_env(args, &[]).await
+    }
+
+    /// Like `run`, with `env` as the only environment variables.
+    pub async fn run_with_env(&self, args: &[&str], env: &[(&str, &str)]) -> Run {
+        let output = Command::new(env!("CARGO_BIN_EXE_tavily-anet-mcp"))
+            .arg("--config")
+            .arg(self.dir.join("config.toml"))
+            .args(args)
+            .env_clear()
+            .envs(env.iter().copied())
+            .current_dir(&self.dir)
+            .stdin(Stdio::null())
+            .output()
+            .await
+            .expect("server binary runs");
+
+        Run {
+            success: output.status.success(),
+            stdout: String::from_utf8_lossy(&output.stdout).into_owned(),
+            stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+        }
+    }
+
+    /// Starts the binary with `args` in the background; it is killed when dropped.
+    pub fn spawn(&self, args: &[&str]) -> Child {
+        Command::new(env!("CARGO_BIN_EXE_tavily-anet-mcp"))
+            .arg("--config")
+            .arg(self.dir.join("config.toml"))
+            .args(args)
+            .env_clear()
+            .current_dir(&self.dir)
+            .stdin(Stdio::null())
+            .kill_on_drop(true)
+            .spawn()
+            .expect("server binary starts")
+    }
+
+    /// Calls `tool` once through the `call` subcommand.
+    pub async fn call(&self, tool: &str, arguments: Value) -> Run {
+        self.run(&["call", tool, "--args", &arguments.to_string()])
+            .await
+    }
+}
+
+impl Drop for Harness {
+    fn drop(&mut self) {
+        std::fs::remove_dir_all(&self.dir).ok();
+    }
+}
+
+// A mock Tavily plus `McpServer` with the tools, reached through the in-process transport
+pub struct Server {
+    pub mock: MockTavily,
+    pub client: TavilyMcpClient,
+    _shutdown: Shutdown,
+}
+
+impl Server {
+    pub async fn start() -> Self {
+        Self::with_config(|_| {}).await
+    }
+
+    /// `configure` adjusts the same settings `Harness` writes to its config
+    /// file, e.g. to add keys or a circuit breaker.
+    pub async fn with_config(configure: impl FnOnce(&mut Config)) -> Self {
+        let mock = MockTavily::start().await.expect("mock Tavily starts");
+
+        let mut config = Config::default();
+        config.tavily.api_key = Some(API_KEY.to_string());
+        config.tavily.base_url = mock.base_url();
+        config.tavily.timeout_secs = 5;
+        config.retry.max_retries = 2;
+        config.retry.initial_backoff_ms = 10;
+        config.retry.max_backoff_ms = 20;
+        configure(&mut config);
+
+        let client = Arc::new(
+            TavilyClient::new(
+                config.api_keys().unwrap(),
+                &config.tavily,
+                &config.retry,
+                &config.rate_limit,
+                &config.circuit_breaker,
+            )
+            .unwrap(),
+        );
+        let tools = build_tools(
+            &config.tools,
+            client,
+            Cache::disabled(),
+            AuditLog::disabled(),
+            Arc::new(UsageAccountant::new(config.budget.clone())),
+            None,
+        );
+        let shutdown = Shutdown::new();
+        let server = McpServer::new(tools, None, &shutdown);
+
+        Self {
+            mock,
+            client: TavilyMcpClient::new(InProcessTransport::new(server)),
+            _shutdown: shutdown,
+        }
+    }
+}
@@ -0,0 +1,174 @@
+//! Snapshot and property tests for the result formatters.
+//!
+//! Every response in `tests/fixtures/formatter` is rendered and compared with
+//! its snapshot in `tests/snapshots`. After an intended output change, review
+//! and accept the new snapshots with `cargo insta review`, or rewrite them all
+//! with `INSTA_UPDATE=always cargo test --test formatter`.
+
+use proptest::prelude::*;
+use serde_json::Map;
+use tavily_anet_mcp::models::tavily::{
+    ExtractResult, FailedResult, TavilyExtractResponse, TavilyResponse, TavilyResult,
+};
+use tavily_anet_mcp::utils::formatter::{
+    format_stale_notice, format_tavily_extract_results, format_tavily_results,
+};
+
+fn load<T: serde::de::DeserializeOwned>(path: &std::path::Path) -> T {
+    let text = std::fs::read_to_string(path).unwrap();
+    serde_json::from_str(&text)
+        .unwrap_or_else(|e| panic!("Invalid fixture {}: {}", path.display(), e))
+}
+
+#[test]
+fn search_snapshots() {
+    insta::glob!("fixtures/formatter/search/*.json", |path| {
+        let response: TavilyResponse = load(path);
+        insta::assert_snapshot!(format_tavily_results(&response));
+    });
+}
+
+#[test]
+fn extract_snapshots() {
+    insta::glob!("fixtures/formatter/extract/*.json", |path| {
+        let response: TavilyExtractResponse = load(path);
+        insta::assert_snapshot!(format_tavily_extract_results(&response));
+    });
+}
+
+#[test]
+fn stale_notice_snapshot() {
+    // 2024-03-01 12:34:56.789 UTC
+    insta::assert_snapshot!(format_stale_notice(1_709_296_496_789));
+}
+
+// Arbitrary text, including empty strings, newlines and non-ASCII characters
+fn text() -> impl Strategy<Value = String> {
+    prop_oneof![
+        Just(String::new()),
+        "[ -~]{0,40}",
+        "\\PC{0,40}",
+        "[a-z \n\t]{0,40}",
+    ]
+}
+
+fn url() -> impl Strategy<Value = String> {
+    prop_oneof![
+        "https://[a-z]{1,12}\\.(com|org|dev)/[a-z0-9/_-]{0,20}",
+        "\\PC{1,30}",
+    ]
+}
+
+fn search_result() -> impl Strategy<Value = TavilyResult> {
+    (
+        text(),
+        url(),
+        proptest::option::of(text()),
+        proptest::option::of(any::<f64>()),
+        proptest::option::of(text()),
+        proptest::option::of(text()),
+    )
+        .prop_map(
+            |(title, url, content, score, published_date, raw_content)| TavilyResult {
+                title,
+                url,
+                content,
+                score,
+                published_date,
+                raw_content,
+                favicon: None,
+                images: None,
+                extra: Map::new(),
+            },
+        )
+}
+
+fn search_response() -> impl Strategy<Value = TavilyResponse> {
+    (
+        text(),
+        proptest::option::of(text()),
+        proptest::collection::vec(search_result(), 0..8),
+        proptest::option::of(text()),
+    )
+        .prop_map(|(query, answer, results, request_id)| TavilyResponse {
+            query,
+            follow_up_questions: None,
+            answer,
+            images: None,
+            results,
+            response_time: None,
+            usage: None,
+            request_id,
+            stale: false,
+            fetched_at: None,
+            extra: Map::new(),
+        })
+}
+
+fn extract_response() -> impl Strategy<Value = TavilyExtractResponse> {
+    let result = (
+        url(),
+        text(),
+        proptest::option::of(proptest::collection::vec(url(), 0..4)),
+    )
+        .prop_map(|(url, raw_content, images)| ExtractResult {
+            url,
+            raw_content,
+            images,
+            favicon: None,
+            extra: Map::new(),
+        });
+    let failed = (url(), text()).prop_map(|(url, error)| FailedResult {
+        url,
+        error,
+        extra: Map::new(),
+    });
+
+    (
+        proptest::collection::vec(result, 0..8),
+        proptest::option::of(proptest::collection::vec(failed, 0..4)),
+        proptest::option::of(any::<f64>()),
+    )
+        .prop_map(
+            |(results, failed_results, response_time)| TavilyExtractResponse {
+                results,
+                failed_results,
+                response_time,
+                usage: None,
+                request_id: None,
+                stale: false,
+                fetched_at: None,
+                extra: Map::new(),
+            },
+        )
+}
+
+proptest! {
+    #[test]
+    fn search_output_lists_every_result_url(response in search_response()) {
+        let output = format_tavily_results(&response);
+        for result in &response.results {
+            prop_assert!(output.contains(&format!("URL: {}", result.url)), "{} missing", result.url);
+        }
+        if let Some(answer) = &response.answer {
+            prop_assert!(output.contains(answer.as_str()));
+        }
+    }
+
+    #[test]
+    fn extract_output_lists_every_url(response in extract_response()) {
+        let output = format_tavily_extract_results(&response);
+        for result in &response.results {
+            prop_assert!(output.contains(&format!("URL: {}", result.url)), "{} missing", result.url);
+        }
+        for failed in response.failed_results.iter().flatten() {
+            prop_assert!(output.contains(&format!("URL: {}", failed.url)), "{} missing", failed.url);
+        }
+    }
+
+    #[test]
+    fn stale_notice_never_panics(fetched_at in any::<u64>()) {
+        let notice = format_stale_notice(fetched_at);
+        prop_assert!(notice.ends_with("may be out of date.\n"));
+    }
+}
@@ -0,0 +1,268 @@
+//! `TavilyMcpClient` against an `McpServer` with the library's tools, through
+//! the in-process transport, with the mock Tavily API upstream.
+
+use mock_tavily::{Fault, MockTavily};
+use serde_json::json;
+use std::sync::Arc;
+use std::time::Duration;
+use tavily_anet_mcp::audit::AuditLog;
+use tavily_anet_mcp::cache::Cache;
+use tavily_anet_mcp::cache::memory::MemoryCache;
+use tavily_anet_mcp::client::TavilyClient;
+use tavily_anet_mcp::config::{CacheBackendKind, Config};
+use tavily_anet_mcp::mcp_client::{InProcessTransport, McpClientError, TavilyMcpClient};
+use tavily_anet_mcp::models::tavily::{
+    Depth, SearchTopic, TavilyExtractRequest, TavilySearchRequest,
+};
+use tavily_anet_mcp::server::McpServer;
+use tavily_anet_mcp::shutdown::Shutdown;
+use tavily_anet_mcp::tools::registry::build_tools;
+use tavily_anet_mcp::usage::UsageAccountant;
+
+struct Fixture {
+    mock: MockTavily,
+    client: TavilyMcpClient,
+    _shutdown: Shutdown,
+}
+
+async fn start() -> Fixture {
+    start_with(|_| {}).await
+}
+
+async fn start_with(configure: impl FnOnce(&mut Config)) -> Fixture {
+    let mock = MockTavily::start().await.unwrap();
+
+    let mut config = Config::default();
+    config.tavily.api_key = Some("tvly-test-key".to_string());
+    config.tavily.base_url = mock.base_url();
+    config.tavily.timeout_secs = 5;
+    config.retry.initial_backoff_ms = 10;
+    config.retry.max_backoff_ms = 20;
+    configure(&mut config);
+
+    let client = Arc::new(
+        TavilyClient::new(
+            config.api_keys().unwrap(),
+            &config.tavily,
+            &config.retry,
+            &config.rate_limit,
+            &config.circuit_breaker,
+        )
+        .unwrap(),
+    );
+    let cache = match config.cache.backend {
+        CacheBackendKind::Memory => Cache::new(
+            Arc::new(MemoryCache::from_config(&config.cache)),
+            &config.cache,
+        ),
+        _ => Cache::disabled(),
+    };
+    let tools = build_tools(
+        &config.tools,
+        client,
+        cache,
+        AuditLog::disabled(),
+        Arc::new(UsageAccountant::new(config.budget.clone())),
+        None,
+    );
+    let shutdown = Shutdown::new();
+    let server = McpServer::new(tools, None, &shutdown);
+
+    Fixture {
+        mock,
+        client: TavilyMcpClient::new(InProcessTransport::new(server))
+            .with_client_info("mcp-client-test", "1.0.0"),
+        _shutdown: shutdown,
+    }
+}
+
+#[tokio::test]
+async fn search_returns_the_typed_response() {
+    let fixture = start().await;
+
+    let mut request = TavilySearchRequest::new("rust");
+    request.search_depth = Some(Depth::Advanced);
+    request.topic = Some(SearchTopic::General);
+    request.include_domains = vec!["rust-lang.org".to_string()];
+    let response = fixture.client.search(&request).await.unwrap();
+
+    assert_eq!(response.query, "rust");
+    assert!(response.answer.unwrap().starts_with("Rust is a systems"));
+    assert_eq!(response.results.len(), 3);
+    assert_eq!(response.results[0].url, "https://www.rust-lang.org/");
+    assert_eq!(response.request_id.as_deref(), Some("mock-search-0001"));
+
+    // Typed fields reach Tavily as they would from any other client, without `_meta`
+    let sent = &fixture.mock.requests_to("search")[0].body;
+    assert_eq!(sent["search_depth"], "advanced");
+    assert_eq!(sent["topic"], "general");
+    assert_eq!(sent["include_domains"], json!(["rust-lang.org"]));
+    assert!(sent.get("max_results").is_none());
+    assert!(sent.get("_meta").is_none());
+}
+
+#[tokio::test]
+async fn extract_returns_the_typed_response() {
+    let fixture = start().await;
+
+    let request = TavilyExtractRequest::new(["https://example.com/", "https://example.org/"]);
+    let response = fixture.client.extract(&request).await.unwrap();
+
+    let urls: Vec<&str> = response.results.iter().map(|r| r.url.as_str()).collect();
+    assert_eq!(urls, ["https://example.com/", "https://example.org/"]);
+    assert!(
+        response.results[0]
+            .raw_content
+            .starts_with("# Example Domain")
+    );
+}
+
+#[tokio::test]
+async fn text_output_is_unchanged_without_the_json_format() {
+    let fixture = start().await;
+
+    let text = fixture
+        .client
+        .call_tool("tavily-search", json!({ "query": "rust" }))
+        .await
+        .unwrap();
+    assert!(text.starts_with("Answer: Rust is a systems programming language"));
+    assert!(text.contains("Detailed Results:"));
+}
+
+#[tokio::test]
+async fn lists_tools() {
+    let fixture = start().await;
+
+    let tools = fixture.client.list_tools().await.unwrap();
+    let names: Vec<&str> = tools.iter().map(|tool| tool.name.as_str()).collect();
+    assert_eq!(names, ["tavily-search", "tavily-extract", "tavily-usage"]);
+    assert_eq!(tools[0].input_schema["required"], json!(["query"]));
+}
+
+#[tokio::test]
+async fn tool_failures_map_to_tool_errors() {
+    let fixture = start().await;
+    let client = fixture.client;
+
+    let error = client
+        .search(&TavilySearchRequest::default())
+        .await
+        .unwrap_err();
+    match error {
+        McpClientError::Tool { tool, message } => {
+            assert_eq!(tool, "tavily-search");
+            assert!(
+                message.contains("query must be at least 1 character(s) long"),
+                "{}",
+                message
+            );
+        }
+        other => panic!("unexpected error: {:?}", other),
+    }
+
+    fixture.mock.set_fault("extract", Some(Fault::Unauthorized));
+    let error = client
+        .extract(&TavilyExtractRequest::new(["https://example.com/"]))
+        .await
+        .unwrap_err();
+    assert!(matches!(error, McpClientError::Tool { .. }), "{:?}", error);
+}
+
+#[tokio::test]
+async fn rpc_errors_keep_their_code() {
+    let fixture = start().await;
+
+    let error = fixture
+        .client
+        .request("resources/list", json!({}))
+        .await
+        .unwrap_err();
+    match error {
+        McpClientError::Rpc { code, message, .. } => {
+            assert_eq!(code, -32601);
+            assert!(message.contains("resources/list"));
+        }
+        other => panic!("unexpected error: {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn slow_responses_time_out() {
+    let fixture = start().await;
+    fixture.mock.set_latency(Duration::from_secs(2));
+
+    let error = fixture
+        .client
+        .with_timeout(Duration::from_millis(200))
+        .search(&TavilySearchRequest::new("rust"))
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(error, McpClientError::Timeout { ref method, .. } if method == "callTool"),
+        "{:?}",
+        error
+    );
+}
+
+#[tokio::test]
+async fn json_output_carries_fields_the_models_do_not_know() {
+    let fixture = start().await;
+    fixture.mock.set_fixture(
+        "search",
+        json!({
+            "query": "rust",
+            "results": [{
+                "title": "Rust",
+                "url": "https://www.rust-lang.org/",
+                "favicon": "https://www.rust-lang.org/favicon.ico",
+                "source_rank": 1
+            }],
+            "auto_parameters": { "topic": "general" },
+            "response_time": 0.9,
+            "usage": { "credits": 1 }
+        }),
+    );
+
+    let response = fixture
+        .client
+        .search(&TavilySearchRequest::new("rust"))
+        .await
+        .unwrap();
+    assert_eq!(
+        response.extra["auto_parameters"],
+        json!({ "topic": "general" })
+    );
+    assert_eq!(response.response_time, Some(0.9));
+    assert_eq!(response.usage.unwrap().credits, Some(1.0));
+
+    let result = &response.results[0];
+    assert!(result.content.is_none());
+    assert_eq!(
+        result.favicon.as_deref(),
+        Some("https://www.rust-lang.org/favicon.ico")
+    );
+    assert_eq!(result.extra["source_rank"], 1);
+}
+
+#[tokio::test]
+async fn stale_json_responses_are_marked() {
+    let fixture = start_with(|config| {
+        config.retry.max_retries = 0;
+        config.cache.backend = CacheBackendKind::Memory;
+        config.cache.ttl_secs = 0;
+    })
+    .await;
+    let request = TavilySearchRequest::new("rust");
+
+    let fresh = fixture.client.search(&request).await.unwrap();
+    assert!(!fresh.stale);
+    assert!(fresh.fetched_at.is_none());
+
+    fixture.mock.fail_next("search", Fault::ServerError);
+    let stale = fixture.client.search(&request).await.unwrap();
+    assert!(stale.stale);
+    assert!(stale.fetched_at.is_some());
+    assert!(stale.extra.is_empty());
+    assert_eq!(stale.results.len(), fresh.results.len());
+}
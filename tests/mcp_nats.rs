@@ -0,0 +1,72 @@
+//! Runs the typed client against `serve` over NATS. The protocol itself is
+//! covered in-process by `mcp_protocol.rs`; this test needs a NATS server and
+//! is skipped by default:
+//!
+//! ```bash
+//! NATS_URL=nats://localhost:4222 cargo test --test mcp_nats -- --ignored
+//! ```
+
+#![cfg(feature = "nats")]
+
+mod common;
+
+use anyhow::{Context, Result, bail};
+use common::Harness;
+use serde_json::Value;
+use std::time::{Duration, Instant};
+use tavily_anet_mcp::mcp_client::{McpClientError, NatsClientTransport, TavilyMcpClient};
+use tavily_anet_mcp::models::tavily::{TavilyExtractRequest, TavilySearchRequest};
+
+// Starts `serve` on fresh subjects and waits until its health check reports ready
+async fn start_server() -> Result<(Harness, tokio::process::Child, async_nats::Client, String)> {
+    let nats_url = std::env::var("NATS_URL").context("NATS_URL must be set")?;
+    let nats = async_nats::connect(nats_url.as_str()).await?;
+    let subject = format!("mcp.test.{}", nuid::next());
+    let health_subject = format!("{}.health", subject);
+    let harness =
+        Harness::with_config(&format!("\n[health]\nsubject = \"{}\"\n", health_subject)).await;
+
+    let server = harness.spawn(&["--nats-url", &nats_url, "--subject", &subject, "serve"]);
+
+    let started = Instant::now();
+    loop {
+        if started.elapsed() > Duration::from_secs(30) {
+            bail!("Server did not become ready within 30s");
+        }
+        if let Ok(Ok(reply)) = tokio::time::timeout(
+            Duration::from_secs(1),
+            nats.request(health_subject.clone(), "".into()),
+        )
+        .await
+        {
+            let health: Value = serde_json::from_slice(&reply.payload)?;
+            if health["ready"] == true {
+                break;
+            }
+        }
+        tokio::time::sleep(Duration::from_millis(200)).await;
+    }
+
+    Ok((harness, server, nats, subject))
+}
+
+#[tokio::test]
+#[ignore = "needs a NATS server at NATS_URL"]
+async fn typed_client_round_trips_over_nats() -> Result<()> {
+    let (_harness, _server, nats, subject) = start_server().await?;
+    let client = TavilyMcpClient::new(NatsClientTransport::new(nats, &subject).await?);
+
+    let search = client.search(&TavilySearchRequest::new("rust")).await?;
+    assert_eq!(search.results.len(), 3);
+    let extract = client
+        .extract(&TavilyExtractRequest::new(["https://example.com/"]))
+        .await?;
+    assert_eq!(extract.results[0].url, "https://example.com/");
+
+    let error = client
+        .search(&TavilySearchRequest::default())
+        .await
+        .unwrap_err();
+    assert!(matches!(error, McpClientError::Tool { .. }), "{:?}", error);
+    Ok(())
+}
@@ -0,0 +1,221 @@
+//! Drives the MCP protocol against `McpServer` through the in-process
+//! transport, with the mock Tavily API upstream. No broker or network is needed.
+
+use mock_tavily::{Fault, MockTavily};
+use serde_json::json;
+use std::sync::Arc;
+use tavily_anet_mcp::audit::AuditLog;
+use tavily_anet_mcp::cache::Cache;
+use tavily_anet_mcp::client::TavilyClient;
+use tavily_anet_mcp::config::Config;
+use tavily_anet_mcp::mcp_client::{InProcessTransport, McpClientError, TavilyMcpClient};
+use tavily_anet_mcp::server::{McpServer, PROTOCOL_VERSION, SERVER_NAME};
+use tavily_anet_mcp::shutdown::Shutdown;
+use tavily_anet_mcp::tools::registry::build_tools;
+use tavily_anet_mcp::usage::UsageAccountant;
+
+async fn start() -> (MockTavily, Shutdown, TavilyMcpClient) {
+    let mock = MockTavily::start().await.unwrap();
+
+    let mut config = Config::default();
+    config.tavily.api_key = Some("tvly-test-key".to_string());
+    config.tavily.base_url = mock.base_url();
+    config.retry.initial_backoff_ms = 10;
+    config.retry.max_backoff_ms = 20;
+
+    let client = Arc::new(
+        TavilyClient::new(
+            config.api_keys().unwrap(),
+            &config.tavily,
+            &config.retry,
+            &config.rate_limit,
+            &config.circuit_breaker,
+        )
+        .unwrap(),
+    );
+    let tools = build_tools(
+        &config.tools,
+        client,
+        Cache::disabled(),
+        AuditLog::disabled(),
+        Arc::new(UsageAccountant::new(config.budget.clone())),
+        None,
+    );
+    let shutdown = Shutdown::new();
+    let server = McpServer::new(tools, None, &shutdown);
+
+    (
+        mock,
+        shutdown,
+        TavilyMcpClient::new(InProcessTransport::new(server)),
+    )
+}
+
+fn rpc_code(error: McpClientError) -> i64 {
+    match error {
+        McpClientError::Rpc { code, .. } => code,
+        other => panic!("unexpected error: {:?}", other),
+    }
+}
+
+fn tool_message(error: McpClientError) -> String {
+    match error {
+        McpClientError::Tool { message, .. } => message,
+        other => panic!("unexpected error: {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn initialize_list_and_call_every_tool() {
+    let (mock, _shutdown, client) = start().await;
+
+    let initialize = client.initialize().await.unwrap();
+    assert_eq!(initialize["protocolVersion"], PROTOCOL_VERSION);
+    assert_eq!(initialize["serverInfo"]["name"], SERVER_NAME);
+    assert_eq!(
+        initialize["capabilities"],
+        json!({ "tools": {}, "prompts": {}, "resources": {} })
+    );
+
+    let tools = client.list_tools().await.unwrap();
+    let names: Vec<&str> = tools.iter().map(|tool| tool.name.as_str()).collect();
+    assert_eq!(names, ["tavily-search", "tavily-extract", "tavily-usage"]);
+
+    let search = client
+        .call_tool("tavily-search", json!({ "query": "rust" }))
+        .await
+        .unwrap();
+    assert!(search.contains("Detailed Results:"), "{}", search);
+
+    let extract = client
+        .call_tool(
+            "tavily-extract",
+            json!({ "urls": ["https://example.com/"] }),
+        )
+        .await
+        .unwrap();
+    assert!(extract.contains("Extracted Results:"), "{}", extract);
+
+    let usage = client.call_tool("tavily-usage", json!({})).await.unwrap();
+    assert!(usage.contains("Researcher"), "{}", usage);
+
+    assert_eq!(client.request("ping", json!({})).await.unwrap(), json!({}));
+    assert_eq!(mock.requests_to("search").len(), 1);
+}
+
+#[tokio::test]
+async fn protocol_errors_have_json_rpc_codes() {
+    let (mock, _shutdown, client) = start().await;
+
+    let error = client.request("resources/list", json!({})).await;
+    assert_eq!(rpc_code(error.unwrap_err()), -32601);
+
+    let error = client
+        .request("callTool", json!({ "name": "tavily-crawl" }))
+        .await;
+    assert_eq!(rpc_code(error.unwrap_err()), -32602);
+
+    let error = client
+        .request(
+            "callTool",
+            json!({ "name": "tavily-search", "arguments": "rust" }),
+        )
+        .await;
+    assert_eq!(rpc_code(error.unwrap_err()), -32602);
+
+    mock.set_fault("search", Some(Fault::Unauthorized));
+    let error = client
+        .request(
+            "callTool",
+            json!({ "name": "tavily-search", "arguments": { "query": "rust" } }),
+        )
+        .await;
+    assert_eq!(rpc_code(error.unwrap_err()), -32603);
+}
+
+#[tokio::test]
+async fn invalid_arguments_are_rejected_before_calling_tavily() {
+    let (mock, _shutdown, client) = start().await;
+
+    for (tool, arguments, expected) in [
+        (
+            "tavily-search",
+            json!({}),
+            "Missing required argument query",
+        ),
+        (
+            "tavily-search",
+            json!({ "query": "rust", "search_depth": "deep" }),
+            "search_depth must be one of",
+        ),
+        (
+            "tavily-search",
+            json!({ "query": "rust", "max_results": 50 }),
+            "max_results must be at most 20",
+        ),
+        (
+            "tavily-search",
+            json!({ "query": "rust", "include_domains": "rust-lang.org" }),
+            "include_domains must be of type array",
+        ),
+        (
+            "tavily-extract",
+            json!({ "urls": [] }),
+            "urls must have at least 1 item(s)",
+        ),
+        (
+            "tavily-extract",
+            json!({ "urls": ["https://example.com/"], "extract_depth": 1 }),
+            "extract_depth must be of type string",
+        ),
+    ] {
+        let message = tool_message(client.call_tool(tool, arguments).await.unwrap_err());
+        assert!(message.starts_with("Invalid"), "{}", message);
+        assert!(message.contains(expected), "{}", message);
+    }
+
+    assert!(mock.requests().is_empty());
+}
+
+#[tokio::test]
+async fn upstream_failures_are_retried_then_reported() {
+    let (mock, _shutdown, client) = start().await;
+
+    mock.fail_next("search", Fault::ServerError);
+    let search = client
+        .call_tool("tavily-search", json!({ "query": "rust" }))
+        .await
+        .unwrap();
+    assert!(search.contains("Detailed Results:"), "{}", search);
+    assert_eq!(mock.requests_to("search").len(), 2);
+
+    mock.set_fault("extract", Some(Fault::Unauthorized));
+    let message = tool_message(
+        client
+            .call_tool(
+                "tavily-extract",
+                json!({ "urls": ["https://example.com/"] }),
+            )
+            .await
+            .unwrap_err(),
+    );
+    assert!(
+        message.contains("missing or invalid API key"),
+        "{}",
+        message
+    );
+    assert_eq!(mock.requests_to("extract").len(), 1);
+}
+
+#[tokio::test]
+async fn draining_servers_refuse_new_requests() {
+    let (mock, shutdown, client) = start().await;
+
+    shutdown.start_draining();
+    let error = client
+        .call_tool("tavily-search", json!({ "query": "rust" }))
+        .await
+        .unwrap_err();
+    assert!(error.to_string().contains("shutting down"), "{}", error);
+    assert!(mock.requests().is_empty());
+}
@@ -0,0 +1,271 @@
+//! The HTTP and stdio client transports. HTTP requests reach an `McpServer`
+//! served by axum with the mock Tavily API upstream; the stdio transport talks
+//! to small child processes that echo or swallow its requests.
+
+use axum::extract::State;
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::post;
+use axum::{Json, Router, Server};
+use mock_tavily::{Fault, MockTavily};
+use serde_json::{Value, json};
+use std::net::TcpListener;
+use std::sync::Arc;
+use std::time::Duration;
+use tavily_anet_mcp::audit::AuditLog;
+use tavily_anet_mcp::cache::Cache;
+use tavily_anet_mcp::client::TavilyClient;
+use tavily_anet_mcp::config::{ClientKeyPolicy, Config};
+use tavily_anet_mcp::mcp_client::{
+    ClientTransport, HttpClientTransport, McpClientError, StdioClientTransport, TavilyMcpClient,
+};
+use tavily_anet_mcp::models::tavily::TavilySearchRequest;
+use tavily_anet_mcp::server::McpServer;
+use tavily_anet_mcp::shutdown::Shutdown;
+use tavily_anet_mcp::tools::registry::build_tools;
+use tavily_anet_mcp::transport::meta_from_headers;
+use tavily_anet_mcp::usage::UsageAccountant;
+use tokio::process::Command;
+
+// Serves `McpServer` over HTTP the way a server transport would: request
+// headers become `_meta` fields and notifications get an empty answer
+async fn rpc(
+    State(server): State<McpServer>,
+    headers: HeaderMap,
+    Json(request): Json<Value>,
+) -> Response {
+    let meta = meta_from_headers(|name| {
+        headers
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string)
+    });
+    match server.handle(request, meta).await {
+        Some(response) => Json(response).into_response(),
+        None => StatusCode::ACCEPTED.into_response(),
+    }
+}
+
+/// Serves `app` on a free local port and returns its `/mcp` URL.
+fn serve(app: Router) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/mcp", listener.local_addr().unwrap());
+    let server = Server::from_tcp(listener)
+        .unwrap()
+        .serve(app.into_make_service());
+    tokio::spawn(server);
+    url
+}
+
+struct Fixture {
+    mock: MockTavily,
+    url: String,
+    _shutdown: Shutdown,
+}
+
+async fn start() -> Fixture {
+    let mock = MockTavily::start().await.unwrap();
+
+    let mut config = Config::default();
+    config.tavily.api_key = Some("tvly-test-key".to_string());
+    config.tavily.base_url = mock.base_url();
+    config.tavily.client_key_policy = ClientKeyPolicy::Allow;
+    config.tavily.timeout_secs = 5;
+    config.retry.initial_backoff_ms = 10;
+    config.retry.max_backoff_ms = 20;
+
+    let client = Arc::new(
+        TavilyClient::new(
+            config.api_keys().unwrap(),
+            &config.tavily,
+            &config.retry,
+            &config.rate_limit,
+            &config.circuit_breaker,
+        )
+        .unwrap(),
+    );
+    let tools = build_tools(
+        &config.tools,
+        client,
+        Cache::disabled(),
+        AuditLog::disabled(),
+        Arc::new(UsageAccountant::new(config.budget.clone())),
+        None,
+    );
+    let shutdown = Shutdown::new();
+    let server = McpServer::new(tools, None, &shutdown);
+
+    let url = serve(Router::new().route("/mcp", post(rpc)).with_state(server));
+    Fixture {
+        mock,
+        url,
+        _shutdown: shutdown,
+    }
+}
+
+#[tokio::test]
+async fn http_calls_the_tools_of_a_served_mcp_server() {
+    let fixture = start().await;
+    let client = TavilyMcpClient::new(HttpClientTransport::new(&fixture.url));
+
+    let initialize = client.initialize().await.unwrap();
+    assert_eq!(initialize["capabilities"]["tools"], json!({}));
+
+    let tools = client.list_tools().await.unwrap();
+    assert_eq!(tools.len(), 3);
+
+    let response = client
+        .search(&TavilySearchRequest::new("rust"))
+        .await
+        .unwrap();
+    assert_eq!(response.query, "rust");
+    assert_eq!(fixture.mock.requests_to("search").len(), 1);
+}
+
+#[tokio::test]
+async fn http_headers_reach_the_server_as_meta() {
+    let fixture = start().await;
+    let transport = HttpClientTransport::new(&fixture.url)
+        .with_header("Tavily-Api-Key", "tvly-own-key")
+        .unwrap();
+
+    TavilyMcpClient::new(transport)
+        .call_tool("tavily-search", json!({ "query": "rust" }))
+        .await
+        .unwrap();
+    let requests = fixture.mock.requests_to("search");
+    assert_eq!(requests[0].api_key.as_deref(), Some("tvly-own-key"));
+}
+
+#[tokio::test]
+async fn http_errors_map_like_the_other_transports() {
+    let fixture = start().await;
+    let client = TavilyMcpClient::new(HttpClientTransport::new(&fixture.url));
+
+    let error = client
+        .request("resources/list", json!({}))
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(error, McpClientError::Rpc { code: -32601, .. }),
+        "{:?}",
+        error
+    );
+
+    fixture.mock.set_fault("search", Some(Fault::Unauthorized));
+    let error = client
+        .call_tool("tavily-search", json!({ "query": "rust" }))
+        .await
+        .unwrap_err();
+    assert!(matches!(error, McpClientError::Tool { .. }), "{:?}", error);
+}
+
+#[tokio::test]
+async fn http_answers_to_another_request_are_transport_errors() {
+    let url = serve(Router::new().route(
+        "/mcp",
+        post(|| async { Json(json!({ "jsonrpc": "2.0", "id": "other", "result": {} })) }),
+    ));
+
+    let error = TavilyMcpClient::new(HttpClientTransport::new(&url))
+        .request("ping", json!({}))
+        .await
+        .unwrap_err();
+    assert!(matches!(error, McpClientError::Transport(_)), "{:?}", error);
+    assert!(
+        error
+            .to_string()
+            .contains("answered request 1 with response other"),
+        "{}",
+        error
+    );
+}
+
+#[tokio::test]
+async fn http_answers_without_json_rpc_are_transport_errors() {
+    let url = serve(Router::new().route(
+        "/mcp",
+        post(|| async { (StatusCode::BAD_GATEWAY, "upstream down") }),
+    ));
+
+    let error = TavilyMcpClient::new(HttpClientTransport::new(&url))
+        .request("ping", json!({}))
+        .await
+        .unwrap_err();
+    assert!(error.to_string().contains("HTTP 502"), "{}", error);
+}
+
+#[tokio::test]
+async fn unreachable_http_servers_are_transport_errors() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/mcp", listener.local_addr().unwrap());
+    drop(listener);
+
+    let error = TavilyMcpClient::new(HttpClientTransport::new(&url))
+        .list_tools()
+        .await
+        .unwrap_err();
+    assert!(matches!(error, McpClientError::Transport(_)), "{:?}", error);
+}
+
+// The clock is paused, so the timeout passes as soon as nothing else is left to run
+#[tokio::test(start_paused = true)]
+async fn slow_http_servers_time_out() {
+    let fixture = start().await;
+    fixture.mock.set_latency(Duration::from_secs(60));
+
+    let error = TavilyMcpClient::new(HttpClientTransport::new(&fixture.url))
+        .with_timeout(Duration::from_secs(1))
+        .search(&TavilySearchRequest::new("rust"))
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(error, McpClientError::Timeout { .. }),
+        "{:?}",
+        error
+    );
+}
+
+#[tokio::test]
+async fn stdio_matches_responses_to_requests_by_id() {
+    // `cat` echoes every request back, which is enough to exercise the routing
+    let transport = StdioClientTransport::spawn(Command::new("cat")).unwrap();
+
+    let (first, second) = tokio::join!(
+        transport.request("1", json!({ "id": "1", "method": "first" })),
+        transport.request("2", json!({ "id": 2, "method": "second" })),
+    );
+    assert_eq!(first.unwrap()["method"], "first");
+    assert_eq!(second.unwrap()["method"], "second");
+}
+
+#[tokio::test(start_paused = true)]
+async fn silent_stdio_servers_time_out() {
+    let mut command = Command::new("sh");
+    command.args(["-c", "cat > /dev/null"]);
+    let transport = StdioClientTransport::spawn(command).unwrap();
+
+    let error = TavilyMcpClient::new(transport)
+        .with_timeout(Duration::from_secs(1))
+        .request("ping", json!({}))
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(error, McpClientError::Timeout { .. }),
+        "{:?}",
+        error
+    );
+}
+
+#[tokio::test]
+async fn exited_stdio_servers_are_transport_errors() {
+    let transport = StdioClientTransport::spawn(Command::new("true")).unwrap();
+    let client = TavilyMcpClient::new(transport).with_timeout(Duration::from_secs(30));
+
+    // Whether the write or the wait for the answer fails, it fails without
+    // waiting out the timeout
+    for _ in 0..2 {
+        let error = client.request("ping", json!({})).await.unwrap_err();
+        assert!(matches!(error, McpClientError::Transport(_)), "{:?}", error);
+    }
+}
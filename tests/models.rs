@@ -0,0 +1,181 @@
+//! Deserialization of the recorded Tavily responses in `tests/fixtures/formatter`,
+//! including fields the models don't know about and fields Tavily left out or
+//! sent as `null`.
+
+use serde::de::DeserializeOwned;
+use serde_json::{Value, json};
+use tavily_anet_mcp::models::tavily::{
+    TavilyExtractResponse, TavilyImage, TavilyResponse, TavilyUsageResponse,
+};
+
+fn fixture(name: &str) -> Value {
+    let path = format!(
+        "{}/tests/fixtures/formatter/{}",
+        env!("CARGO_MANIFEST_DIR"),
+        name
+    );
+    let text = std::fs::read_to_string(&path).unwrap();
+    serde_json::from_str(&text).unwrap()
+}
+
+fn parse<T: DeserializeOwned>(name: &str) -> T {
+    serde_json::from_value(fixture(name))
+        .unwrap_or_else(|e| panic!("Failed to deserialize {}: {}", name, e))
+}
+
+#[test]
+fn search_response_with_every_field() {
+    let response: TavilyResponse = parse("search/all_fields.json");
+
+    assert_eq!(response.query, "Who is Leo Messi?");
+    assert!(response.follow_up_questions.is_none());
+    assert_eq!(response.response_time, Some(1.67));
+    assert_eq!(response.usage.unwrap().credits, Some(1.0));
+    assert_eq!(
+        response.request_id.as_deref(),
+        Some("123e4567-e89b-12d3-a456-426614174111")
+    );
+    assert!(matches!(
+        &response.images.unwrap()[1],
+        TavilyImage::Object {
+            description: Some(_),
+            ..
+        }
+    ));
+
+    let first = &response.results[0];
+    assert_eq!(first.score, Some(0.81025416));
+    assert!(first.raw_content.is_none());
+    assert_eq!(
+        first.favicon.as_deref(),
+        Some("https://en.wikipedia.org/static/favicon/wikipedia.ico")
+    );
+    assert!(matches!(
+        first.images.as_deref(),
+        Some([TavilyImage::String(url)]) if url == "https://upload.wikimedia.org/messi.jpg"
+    ));
+    assert_eq!(
+        response.results[1].published_date.as_deref(),
+        Some("2024-12-18")
+    );
+
+    // Fields added by Tavily after these models were written
+    assert_eq!(
+        response.extra["auto_parameters"],
+        json!({ "topic": "general", "search_depth": "basic" })
+    );
+    assert_eq!(response.results[1].extra["source_rank"], 2);
+    assert!(response.results[0].extra.is_empty());
+}
+
+#[test]
+fn search_results_may_lack_content_and_score() {
+    let response: TavilyResponse = parse("search/missing_fields.json");
+
+    assert_eq!(response.query, "");
+    assert_eq!(response.results.len(), 2);
+    assert_eq!(response.results[0].title, "");
+    assert!(response.results[0].content.is_none());
+    assert!(response.results[0].score.is_none());
+    assert!(response.results[1].content.is_some());
+    assert!(response.results[1].score.is_none());
+}
+
+#[test]
+fn extract_response_with_every_field() {
+    let response: TavilyExtractResponse = parse("extract/all_fields.json");
+
+    let result = &response.results[0];
+    assert!(result.raw_content.starts_with("Artificial intelligence"));
+    assert_eq!(result.images.as_ref().unwrap().len(), 1);
+    assert!(result.favicon.is_some());
+    assert_eq!(result.extra["content_type"], "text/html");
+
+    let failed = &response.failed_results.as_ref().unwrap()[0];
+    assert_eq!(failed.error, "Failed to fetch url");
+    assert_eq!(failed.extra["status_code"], 404);
+
+    assert_eq!(response.response_time, Some(0.02));
+    assert_eq!(response.usage.unwrap().credits, Some(1.0));
+    assert_eq!(
+        response.extra["warnings"],
+        json!(["extract_depth basic was used"])
+    );
+}
+
+#[test]
+fn extract_results_may_lack_everything_but_the_url() {
+    let response: TavilyExtractResponse = parse("extract/missing_fields.json");
+
+    assert_eq!(response.results[0].url, "https://example.com/");
+    assert_eq!(response.results[0].raw_content, "");
+    assert!(response.failed_results.is_none());
+    assert!(response.response_time.is_none());
+}
+
+#[test]
+fn null_fields_read_as_missing() {
+    let search: TavilyResponse = parse("search/null_fields.json");
+    assert_eq!(search.query, "");
+    assert!(search.answer.is_none());
+    assert!(search.usage.is_none());
+    assert_eq!(search.results[0].title, "");
+    assert!(search.results[0].content.is_none());
+    assert!(search.results[0].raw_content.is_none());
+    assert_eq!(search.results[1].title, "Has a title");
+
+    let extract: TavilyExtractResponse = parse("extract/null_fields.json");
+    assert_eq!(extract.results[0].raw_content, "");
+    assert!(extract.results[0].images.is_none());
+    let failed = &extract.failed_results.unwrap()[0];
+    assert_eq!(failed.url, "https://example.org/");
+    assert_eq!(failed.error, "");
+}
+
+#[test]
+fn usage_keeps_fields_the_models_do_not_know() {
+    let usage: TavilyUsageResponse = serde_json::from_value(json!({
+        "key": { "usage": 150, "limit": null, "search_usage": 100 },
+        "account": { "current_plan": "Researcher", "plan_usage": 500, "plan_limit": 15000, "crawl_usage": 20 },
+        "billing_cycle": "monthly"
+    }))
+    .unwrap();
+
+    let key = usage.key.as_ref().unwrap();
+    assert!(key.limit.is_none());
+    assert_eq!(key.extra["search_usage"], 100);
+    assert_eq!(usage.account.as_ref().unwrap().extra["crawl_usage"], 20);
+    assert_eq!(usage.extra["billing_cycle"], "monthly");
+    assert_eq!(usage.remaining_credits(), Some(14_500));
+
+    let serialized = serde_json::to_value(&usage).unwrap();
+    assert_eq!(serialized["key"]["search_usage"], 100);
+    assert_eq!(serialized["billing_cycle"], "monthly");
+}
+
+#[test]
+fn unknown_fields_survive_serialization() {
+    let search: TavilyResponse = parse("search/all_fields.json");
+    let serialized = serde_json::to_value(&search).unwrap();
+    let original = fixture("search/all_fields.json");
+    assert_eq!(serialized["auto_parameters"], original["auto_parameters"]);
+    assert_eq!(serialized["results"][1]["source_rank"], 2);
+    assert_eq!(
+        serialized["results"][0]["favicon"],
+        original["results"][0]["favicon"]
+    );
+    assert_eq!(serialized["response_time"], original["response_time"]);
+    // Nulls are dropped rather than passed on
+    assert!(serialized.get("follow_up_questions").is_none());
+
+    let extract: TavilyExtractResponse = parse("extract/all_fields.json");
+    let serialized = serde_json::to_value(&extract).unwrap();
+    let original = fixture("extract/all_fields.json");
+    assert_eq!(serialized["warnings"], original["warnings"]);
+    assert_eq!(serialized["results"][0]["content_type"], "text/html");
+    assert_eq!(serialized["failed_results"][0]["status_code"], 404);
+
+    // And a second round trip changes nothing
+    let again: TavilyExtractResponse = serde_json::from_value(serialized.clone()).unwrap();
+    assert_eq!(serde_json::to_value(&again).unwrap(), serialized);
+}
@@ -0,0 +1,102 @@
+//! Stale cache fallback of the search tool against the mock Tavily API, with
+//! entries that expire as soon as they are written. The clock is paused, so
+//! waiting out the one second refresh interval takes no time.
+
+use anet_mcp_server::Tool;
+use mock_tavily::{Fault, MockTavily};
+use serde_json::{Value, json};
+use std::sync::Arc;
+use std::time::Duration;
+use tavily_anet_mcp::cache::Cache;
+use tavily_anet_mcp::cache::memory::MemoryCache;
+use tavily_anet_mcp::client::TavilyClient;
+use tavily_anet_mcp::config::{CacheBackendKind, ClientKeyPolicy, Config};
+use tavily_anet_mcp::tools::registry::content_to_text;
+use tavily_anet_mcp::tools::search::TavilySearchTool;
+
+const STALE_NOTICE: &str = "Note: Tavily is currently unavailable.";
+
+async fn start() -> (MockTavily, TavilySearchTool) {
+    let mock = MockTavily::start().await.unwrap();
+
+    let mut config = Config::default();
+    config.tavily.api_key = Some("tvly-test-key".to_string());
+    config.tavily.base_url = mock.base_url();
+    config.tavily.client_key_policy = ClientKeyPolicy::Allow;
+    config.retry.max_retries = 0;
+    config.cache.backend = CacheBackendKind::Memory;
+    config.cache.ttl_secs = 0;
+    config.cache.revalidate_interval_secs = 1;
+
+    let client = Arc::new(
+        TavilyClient::new(
+            config.api_keys().unwrap(),
+            &config.tavily,
+            &config.retry,
+            &config.rate_limit,
+            &config.circuit_breaker,
+        )
+        .unwrap(),
+    );
+    let cache = Cache::new(
+        Arc::new(MemoryCache::from_config(&config.cache)),
+        &config.cache,
+    );
+
+    (mock, TavilySearchTool::new(client).with_cache(cache))
+}
+
+async fn search(tool: &TavilySearchTool, arguments: Value) -> anyhow::Result<String> {
+    let content = tool.call(Some(arguments)).await?;
+    Ok(content_to_text(&content))
+}
+
+#[tokio::test(start_paused = true)]
+async fn server_errors_are_answered_from_the_stale_cache_and_refreshed() {
+    let (mock, tool) = start().await;
+    search(&tool, json!({ "query": "rust" })).await.unwrap();
+    mock.fail_next("search", Fault::ServerError);
+
+    let text = search(&tool, json!({ "query": "rust" })).await.unwrap();
+    assert!(text.starts_with(STALE_NOTICE), "{}", text);
+
+    // The server's key refreshes the entry once Tavily answers again
+    tokio::time::sleep(Duration::from_millis(1500)).await;
+    assert_eq!(mock.requests_to("search").len(), 3);
+}
+
+#[tokio::test]
+async fn rejected_requests_are_not_answered_from_the_stale_cache() {
+    let (mock, tool) = start().await;
+    search(&tool, json!({ "query": "rust" })).await.unwrap();
+
+    for fault in [Fault::Unauthorized, Fault::RateLimited] {
+        mock.fail_next("search", fault);
+        let error = search(&tool, json!({ "query": "rust" }))
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(error.starts_with("Tavily API error"), "{}", error);
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn client_keys_get_stale_results_without_a_refresh() {
+    let (mock, tool) = start().await;
+    search(&tool, json!({ "query": "rust" })).await.unwrap();
+    mock.fail_next("search", Fault::ServerError);
+
+    let text = search(
+        &tool,
+        json!({ "query": "rust", "_meta": { "tavily_api_key": "tvly-own-key" } }),
+    )
+    .await
+    .unwrap();
+    assert!(text.starts_with(STALE_NOTICE), "{}", text);
+
+    // A refresh would spend the server's credits on the caller's behalf
+    tokio::time::sleep(Duration::from_millis(1500)).await;
+    let requests = mock.requests_to("search");
+    assert_eq!(requests.len(), 2);
+    assert_eq!(requests[1].api_key.as_deref(), Some("tvly-own-key"));
+}
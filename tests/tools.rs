@@ -0,0 +1,249 @@
+//! End-to-end tool calls through `McpServer` and the in-process transport
+//! against the mock Tavily API. No network access or API key is needed.
+
+mod common;
+
+use common::{API_KEY, Server};
+use mock_tavily::Fault;
+use serde_json::{Value, json};
+use std::time::Duration;
+
+#[tokio::test]
+async fn lists_every_tool_with_its_schema() {
+    let server = Server::start().await;
+
+    let tools = server.client.list_tools().await.unwrap();
+    let names: Vec<&str> = tools.iter().map(|tool| tool.name.as_str()).collect();
+    assert_eq!(names, ["tavily-search", "tavily-extract", "tavily-usage"]);
+
+    for tool in &tools {
+        assert!(!tool.description.is_empty());
+        assert_eq!(tool.input_schema["type"], "object");
+    }
+    assert_eq!(tools[0].input_schema["required"], json!(["query"]));
+    assert_eq!(tools[1].input_schema["required"], json!(["urls"]));
+    assert!(server.mock.requests().is_empty());
+}
+
+#[tokio::test]
+async fn search_formats_answer_and_results() {
+    let server = Server::start().await;
+
+    let text = server
+        .client
+        .call_tool(
+            "tavily-search",
+            json!({ "query": "rust language", "max_results": 5 }),
+        )
+        .await
+        .unwrap();
+
+    assert!(text.starts_with("Answer: Rust is a systems programming language"));
+    assert!(text.contains("\nSources:\n- Rust Programming Language: https://www.rust-lang.org/"));
+    assert!(text.contains("Detailed Results:"));
+    assert!(text.contains("\nTitle: The Rust Programming Language - The Book\nURL: https://doc.rust-lang.org/book/\nContent: "));
+
+    let requests = server.mock.requests_to("search");
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].api_key.as_deref(), Some(API_KEY));
+    assert_eq!(requests[0].body["query"], "rust language");
+    assert_eq!(requests[0].body["max_results"], 5);
+}
+
+#[tokio::test]
+async fn search_adds_news_topic_for_news_queries() {
+    let server = Server::start().await;
+
+    server
+        .client
+        .call_tool("tavily-search", json!({ "query": "rust news" }))
+        .await
+        .unwrap();
+
+    assert_eq!(server.mock.requests_to("search")[0].body["topic"], "news");
+}
+
+#[tokio::test]
+async fn extract_formats_each_url() {
+    let server = Server::start().await;
+
+    let urls = ["https://example.com/", "https://example.org/"];
+    let text = server
+        .client
+        .call_tool("tavily-extract", json!({ "urls": urls }))
+        .await
+        .unwrap();
+
+    assert!(text.starts_with("Extracted Results:"));
+    for url in urls {
+        assert!(text.contains(&format!("\nURL: {}\nRaw Content: # Example Domain", url)));
+    }
+    assert!(text.ends_with("Response Time: 0.42 seconds"), "{}", text);
+}
+
+#[tokio::test]
+async fn usage_reports_account_and_server_totals() {
+    let server = Server::start().await;
+
+    let usage = server.client.usage().await.unwrap();
+    assert_eq!(usage["account"][0]["label"], "key-1");
+    assert_eq!(
+        usage["account"][0]["usage"]["account"]["current_plan"],
+        "Researcher"
+    );
+    assert_eq!(usage["account"][0]["usage"]["key"]["limit"], 1000);
+    assert_eq!(usage["keys"][0]["label"], "key-1");
+    assert!(usage["server"].is_object());
+}
+
+#[tokio::test]
+async fn usage_is_queried_with_every_key() {
+    let server = Server::with_config(|config| {
+        config.tavily.api_keys = vec!["backup:tvly-backup-key".to_string()];
+    })
+    .await;
+
+    let usage = server.client.usage().await.unwrap();
+    let labels: Vec<&str> = usage["account"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|report| report["label"].as_str().unwrap())
+        .collect();
+    assert_eq!(labels, ["key-1", "backup"]);
+
+    let mut keys: Vec<String> = server
+        .mock
+        .requests_to("usage")
+        .into_iter()
+        .filter_map(|request| request.api_key)
+        .collect();
+    keys.sort();
+    assert_eq!(keys, ["tvly-backup-key", API_KEY]);
+}
+
+#[tokio::test]
+async fn missing_parameters_are_reported_without_calling_tavily() {
+    let server = Server::start().await;
+
+    let error = call_error(&server, "tavily-search", json!({})).await;
+    assert!(
+        error.contains("Invalid tavily-search arguments: Missing required argument query"),
+        "{}",
+        error
+    );
+
+    let error = call_error(&server, "tavily-extract", json!({ "urls": [] })).await;
+    assert!(
+        error.contains("urls must have at least 1 item(s)"),
+        "{}",
+        error
+    );
+    assert!(server.mock.requests().is_empty());
+}
+
+#[tokio::test]
+async fn unknown_tools_are_rejected_before_calling_tavily() {
+    let server = Server::start().await;
+
+    let error = call_error(&server, "tavily-crawl", json!({})).await;
+    assert!(error.contains("Unknown tool: tavily-crawl"), "{}", error);
+    assert!(server.mock.requests().is_empty());
+}
+
+#[tokio::test]
+async fn server_errors_are_retried() {
+    let server = Server::start().await;
+    server.mock.fail_next("search", Fault::ServerError);
+    server.mock.fail_next("search", Fault::RateLimited);
+
+    let text = server
+        .client
+        .call_tool("tavily-search", json!({ "query": "rust" }))
+        .await
+        .unwrap();
+    assert!(text.contains("Detailed Results:"));
+    assert_eq!(server.mock.requests_to("search").len(), 3);
+}
+
+#[tokio::test]
+async fn retries_stop_after_max_retries() {
+    let server = Server::start().await;
+    server.mock.set_fault("search", Some(Fault::ServerError));
+
+    let error = call_error(&server, "tavily-search", json!({ "query": "rust" })).await;
+    assert!(error.contains("Tavily API error"), "{}", error);
+    assert!(error.contains("Internal server error"), "{}", error);
+    assert_eq!(server.mock.requests_to("search").len(), 3);
+}
+
+#[tokio::test]
+async fn rejected_keys_are_not_retried() {
+    let server = Server::start().await;
+    server.mock.fail_next("search", Fault::Unauthorized);
+
+    let error = call_error(&server, "tavily-search", json!({ "query": "rust" })).await;
+    assert!(error.contains("missing or invalid API key"), "{}", error);
+    assert_eq!(server.mock.requests_to("search").len(), 1);
+}
+
+#[tokio::test]
+async fn malformed_responses_are_reported() {
+    let server = Server::start().await;
+    server.mock.fail_next("extract", Fault::MalformedJson);
+
+    let error = call_error(
+        &server,
+        "tavily-extract",
+        json!({ "urls": ["https://example.com/"] }),
+    )
+    .await;
+    assert!(
+        error.contains("Failed to parse Tavily extract response"),
+        "{}",
+        error
+    );
+}
+
+// The clock is paused, so the 5 second request timeout passes as soon as
+// nothing else is left to run
+#[tokio::test(start_paused = true)]
+async fn slow_responses_time_out_and_are_retried() {
+    let server = Server::start().await;
+    server
+        .mock
+        .fail_next("search", Fault::Delay(Duration::from_secs(10)));
+
+    let text = server
+        .client
+        .call_tool("tavily-search", json!({ "query": "rust" }))
+        .await
+        .unwrap();
+    assert!(text.contains("Detailed Results:"));
+    assert_eq!(server.mock.requests_to("search").len(), 2);
+}
+
+#[tokio::test]
+async fn open_circuit_breaker_fails_fast() {
+    let server = Server::with_config(|config| {
+        config.circuit_breaker.min_requests = 2;
+        config.circuit_breaker.failure_rate = 0.5;
+        config.circuit_breaker.open_secs = 60;
+    })
+    .await;
+    server.mock.set_fault("search", Some(Fault::ServerError));
+
+    let error = call_error(&server, "tavily-search", json!({ "query": "rust" })).await;
+    assert!(error.contains("Tavily search is unavailable"), "{}", error);
+    // The third attempt is refused by the breaker instead of reaching Tavily
+    assert_eq!(server.mock.requests_to("search").len(), 2);
+}
+
+async fn call_error(server: &Server, tool: &str, arguments: Value) -> String {
+    server
+        .client
+        .call_tool(tool, arguments)
+        .await
+        .unwrap_err()
+        .to_string()
+}